@@ -1,26 +1,65 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use bt_any_error::any_err::AnyErr;
-use bt_logger::{log_error, log_info, log_warning};
-use bt_yaml_utils::{get_yaml, get_yaml_from_string};
+use bt_logger::{log_error, log_warning};
+use bt_yaml_utils::get_yaml_from_string;
+use serde::de::DeserializeOwned;
 use yaml_rust2::Yaml;
 
 use crate::app_info::AppInfo;
+use crate::app_name::{resolve_app_name, AppNameSource};
+use crate::environment::{resolve_environment, EnvironmentCandidates, EnvironmentSource, ResolvedEnvironment, DEFAULT_RUN_ENV};
+use crate::schema::{environment_block, EnvironmentInfo, ValidationMode, APP_CONFIG_SCHEMA, ENVIRONMENTS_KEY};
+use crate::app_info::{CargoPkg, UNKNOWN_APP_NAME, UNKNOWN_APP_VERSION};
+use crate::config_file::{find_config_file, locate_config_file, read_config_file, ConfigLocation, SearchLocation};
+use crate::config_value::{deserialize_yaml, emit_yaml, flatten_yaml, read_layered_yaml, yaml_map, yaml_opt_str, lookup_key_path, yaml_key_to_string, yaml_scalar_to_string, yaml_type_name};
 use crate::utils::init_app_base_url;
+use crate::migration::{upgrade_config, CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION};
+use crate::version::Version;
 
 const APP_YML_CONFIG: &str = "config/core/app-config.yml";
 const APP_YML_CONFIG_ENV_VAR_NAME: &str = "BT_APP_CONFIGYMLFILE";
 
+pub(crate) const DEFAULT_FILES_APP_DIR: &str = "site";
+pub(crate) const DEFAULT_APP_PATH: &str = "/app";
+pub(crate) const DEFAULT_API_PATH: &str = "/api";
+
 #[derive(Clone, Debug)]
 pub struct AppConfig {
     name: String,
+    name_source: AppNameSource,
     version: String,
     environment: String,
+    environment_source: EnvironmentSource,
     agent: AgentConfig,
     files_app_dir: String,
+    files_app_path: PathBuf,
+    require_index_html: bool,
     app_path: String,
     api_path: String,
-    end_points: HashMap<String, String>
+    end_points: HashMap<String, String>,
+    yaml: Yaml,
+    location: Option<ConfigLocation>,
+}
+
+///Builds an `AppConfig` without a YAML file. Values are validated with the same rules as the YAML loader.
+#[derive(Clone, Debug, Default)]
+pub struct AppConfigBuilder {
+    environment: Option<String>,
+    app_name: Option<String>,
+    version: Option<String>,
+    files_app_dir: Option<String>,
+    require_index_html: Option<bool>,
+    app_path: Option<String>,
+    api_path: Option<String>,
+    agent_host: Option<String>,
+    agent_port: Option<i64>,
+    agent_secure: Option<bool>,
+    agent_end_point: Option<String>,
+    end_points: Vec<(String, Option<String>)>,
 }
 
 #[derive(Clone, Debug)]
@@ -34,29 +73,91 @@ struct AgentConfig{
 impl AppConfig {
     // Constructor to read from YAML file
     pub fn new(running_environment: &str, app_info: &AppInfo, embed_config: Option<&str>) -> Result<Self, AnyErr> {
-        let app_config: Yaml = if let Some(yml_cfg) = embed_config {
-            get_yaml_from_string(yml_cfg)?
-        }else {
-            get_yaml(APP_YML_CONFIG_ENV_VAR_NAME, APP_YML_CONFIG)?
-        };
+        Self::new_with_validation(running_environment, app_info, embed_config, ValidationMode::Lenient)
+    }
+
+    ///Same as `new`. In `ValidationMode::Strict` unknown or misspelled keys are errors instead of logged warnings.
+    pub fn new_with_validation(running_environment: &str, app_info: &AppInfo, embed_config: Option<&str>, validation: ValidationMode) -> Result<Self, AnyErr> {
+        Self::new_with_candidates(&EnvironmentCandidates::explicit(running_environment), app_info, embed_config, validation)
+    }
+
+    ///Same as `new_with_validation`, choosing the environment from `candidates`. Use `EnvironmentCandidates::from_process`
+    /// to also read the `--env`/`-e` command line flag and an env variable such as `BT_ENV`.
+    pub fn new_with_candidates(candidates: &EnvironmentCandidates, app_info: &AppInfo, embed_config: Option<&str>, validation: ValidationMode) -> Result<Self, AnyErr> {
+        let (app_config, location) = Self::read_yaml(embed_config)?;
+        let app_environment = resolve_environment(&app_config, candidates);
+        let ac = Self::from_yaml(app_config, &app_environment, app_info, Some(app_info.version), validation, location)?;
+        init_app_base_url(&ac.app_path);
+        Ok(ac)
+    }
+
+    ///Same as `new_with_validation`, reading the file at `path` instead of the env variable or search paths.
+    /// A relative `files_app_dir` is resolved against the folder of the file.
+    /// `app_version` is checked against `min_app_version`/`max_app_version`; None skips the check (e.g. tools that only check the file).
+    pub fn from_file(running_environment: &str, app_info: &AppInfo, app_version: Option<&str>, path: &Path, validation: ValidationMode) -> Result<Self, AnyErr> {
+        let app_config = read_config_file(path)?;
+        let location = ConfigLocation{ path: path.to_path_buf(), location: SearchLocation::Explicit };
+        let app_environment = resolve_environment(&app_config, &EnvironmentCandidates::explicit(running_environment));
+        let ac = Self::from_yaml(app_config, &app_environment, app_info, app_version, validation, Some(location))?;
+        init_app_base_url(&ac.app_path);
+        Ok(ac)
+    }
+
+    ///Layered configuration: `base_config` (usually embedded in the binary) with the on-disk file merged over it.
+    /// The file is taken from `BT_APP_CONFIGYMLFILE` or the search paths and is optional unless the env variable is set.
+    pub fn new_layered(running_environment: &str, app_info: &AppInfo, base_config: &str, validation: ValidationMode) -> Result<Self, AnyErr> {
+        let (app_config, location) = Self::read_yaml_layered(base_config)?;
+        let app_environment = resolve_environment(&app_config, &EnvironmentCandidates::explicit(running_environment));
+        let ac = Self::from_yaml(app_config, &app_environment, app_info, Some(app_info.version), validation, location)?;
+        init_app_base_url(&ac.app_path);
+        Ok(ac)
+    }
 
-        let app_environment: &str;
-
-        if running_environment.trim().is_empty() || app_config[running_environment].is_badvalue(){
-            log_error!("","Invalid Running Environment '{}'. Will use default to continue.",running_environment);  
-            #[cfg(debug_assertions)]
-                const RUN_ENV: &str = "dev";
-            #[cfg(not(debug_assertions))]
-                const RUN_ENV: &str = "prod";                     
-            app_environment = app_config["environment"].as_str().unwrap_or(RUN_ENV);
-            log_warning!("","Could not find Running Environment '{}'. Using current default '{}' to continue.",running_environment, app_environment);                    
-        }else{
-            app_environment = running_environment;
-            log_info!("","Using current environment '{}'.",&app_environment);
+    ///Builder to construct the configuration programmatically (e.g. in unit tests)
+    pub fn builder() -> AppConfigBuilder {
+        AppConfigBuilder::default()
+    }
+
+    ///Reads the app-config YAML and the file location (None for embedded content).
+    /// embed_config: Content of the YML config file. None to use env variable or search paths.
+    pub(crate) fn read_yaml(embed_config: Option<&str>) -> Result<(Yaml, Option<ConfigLocation>), AnyErr> {
+        match embed_config {
+            Some(yml_cfg) => Ok((get_yaml_from_string(yml_cfg)?, None)),
+            None => {
+                let location = locate_config_file(APP_YML_CONFIG_ENV_VAR_NAME, APP_YML_CONFIG)?;
+                Ok((read_config_file(&location.path)?, Some(location)))
+            },
+        }
+    }
+
+    ///Reads `base_config` with the app-config file (env variable or search paths), if present, merged over it.
+    pub(crate) fn read_yaml_layered(base_config: &str) -> Result<(Yaml, Option<ConfigLocation>), AnyErr> {
+        let location = find_config_file(APP_YML_CONFIG_ENV_VAR_NAME, APP_YML_CONFIG)?;
+        Ok((read_layered_yaml(&APP_CONFIG_SCHEMA, base_config, location.as_ref().map(|l| l.path.as_path()))?, location))
+    }
+
+    ///Builds the configuration of `environment`, which is expected to be already resolved.
+    /// `app_version` is the running application version checked against the file (None to skip the check).
+    /// `location` is the file the YAML was read from (None for embedded content); it is the base of a relative `files_app_dir`.
+    pub(crate) fn from_yaml(app_config: Yaml, environment: &ResolvedEnvironment, app_info: &AppInfo, app_version: Option<&str>, validation: ValidationMode, location: Option<ConfigLocation>) -> Result<Self, AnyErr> {
+        let app_config = upgrade_config(&APP_CONFIG_SCHEMA, app_config)?;
+        let app_environment = environment.name.as_str();
+        if let Err(e) = APP_CONFIG_SCHEMA.enforce(&app_config, app_environment, validation) {
+            log_error!("","{}",e);
+            return Err(e.into());
         }
 
+        if let Some(app_version) = app_version && let Err(e) = check_app_version(&app_config, app_version) {
+            if validation == ValidationMode::Strict {
+                log_error!("","{}",e);
+                return Err(e.into());
+            }
+            log_warning!("","{}",e);
+        }
+
+        let env_block = environment_block(&app_config, app_environment);
         let mut end_points = HashMap::new();
-        for ep_value in app_config[app_environment]["end_points"].clone() {
+        for ep_value in env_block["end_points"].clone() {
             end_points.insert(
                 ep_value["id"].as_str().unwrap().to_string(),
                 ep_value["path"].as_str().unwrap_or(&format!("/{}",&ep_value["id"].as_str().unwrap().to_string())).to_string(),
@@ -65,51 +166,249 @@ impl AppConfig {
 
         //Location of the Remote AI Agent
         let agent_cfg = AgentConfig{
-            host: app_config[app_environment]["agent"]["host"].as_str().map(|s| s.to_string()), //.unwrap_or(DEFAULT_AGENT_HOST).to_owned(),
-            port: app_config[app_environment]["agent"]["port"].as_i64(), //.unwrap_or(DEFAULT_AGENT_PORT.into()).try_into().unwrap_or(DEFAULT_AGENT_PORT),
-            secure: app_config[app_environment]["agent"]["secure"].as_bool(), //.unwrap_or(true),
-            end_point: app_config[app_environment]["agent"]["end_point"].as_str().unwrap_or("").to_owned(),
+            host: env_block["agent"]["host"].as_str().map(|s| s.to_string()), //.unwrap_or(DEFAULT_AGENT_HOST).to_owned(),
+            port: env_block["agent"]["port"].as_i64(), //.unwrap_or(DEFAULT_AGENT_PORT.into()).try_into().unwrap_or(DEFAULT_AGENT_PORT),
+            secure: env_block["agent"]["secure"].as_bool(), //.unwrap_or(true),
+            end_point: env_block["agent"]["end_point"].as_str().unwrap_or("").to_owned(),
         };
 
         //Application Information
-        let app_name = app_config["app_name"]
-            .as_str()
-            .unwrap_or(app_info.package_name);
+        let app_name = resolve_app_name(&app_config, app_environment, app_info);
         let app_ver = app_info.version;
         
-        let app_path = app_config[app_environment]["app_path"]
+        let app_path = env_block["app_path"]
                 .as_str()
-                .unwrap_or("/app")
+                .unwrap_or(DEFAULT_APP_PATH)
                 .to_string();
-        
-        init_app_base_url(&app_path);
+
+        //Static files directory
+        let files_app_dir = env_block["files_app_dir"]
+                .as_str()
+                .unwrap_or(DEFAULT_FILES_APP_DIR)
+                .to_string();
+        let require_index_html = env_block["require_index_html"].as_bool().unwrap_or(false);
+        let files_app_path = resolve_files_app_dir(&files_app_dir, location.as_ref());
+        //Only applications that serve static files require the directory (see `check_files_app_path`)
+        if require_index_html && let Err(e) = check_files_app_dir(&files_app_path, true) {
+            if validation == ValidationMode::Strict {
+                log_error!("","{}",e);
+                return Err(e.into());
+            }
+            log_warning!("","{}",e);
+        }
 
         Ok(Self {
-            name: app_name.to_owned(),
+            name: app_name.name,
+            name_source: app_name.source,
             version: app_ver.to_owned(),
             environment: app_environment.to_owned(),
-            files_app_dir: app_config[app_environment]["files_app_dir"]
-                .as_str()
-                .unwrap_or("site")
-                .to_string(),
+            environment_source: environment.source.clone(),
+            files_app_dir,
+            files_app_path,
+            require_index_html,
             app_path,
-            api_path: app_config[app_environment]["api_path"]
+            api_path: env_block["api_path"]
                 .as_str()
-                .unwrap_or("/api")
+                .unwrap_or(DEFAULT_API_PATH)
                 .to_string(),
             end_points,
             agent: agent_cfg,
+            yaml: app_config,
+            location,
         })
     }
 
+    ///Environments defined in the app-config file and whether they are empty.
+    /// embed_config: Content of the YML config file. None to use env variable or search paths.
+    pub fn list_environments(embed_config: Option<&str>) -> Result<Vec<EnvironmentInfo>, AnyErr> {
+        Ok(APP_CONFIG_SCHEMA.list_environments(&Self::read_yaml(embed_config)?.0))
+    }
+
+    ///JSON Schema (draft-07) of the app-config YAML file. Useful for editor completion and validation.
+    pub fn json_schema() -> String {
+        serde_json::to_string_pretty(&APP_CONFIG_SCHEMA.to_json_schema()).unwrap_or_default()
+    }
+
+    ///File the configuration was read from and the search location that found it. None for embedded or built configurations.
+    pub fn get_config_location(&self) -> Option<&ConfigLocation> {
+        self.location.as_ref()
+    }
+
+    ///Effective values of the running environment flattened by dotted key path (e.g. `agent.port`, `end_points.chat`).
+    /// Includes defaults and custom sections.
+    pub fn effective_values(&self) -> BTreeMap<String, String> {
+        let mut values = BTreeMap::new();
+        values.insert("app_name".to_owned(), self.name.clone());
+        values.insert("version".to_owned(), self.version.clone());
+        values.insert("environment".to_owned(), self.environment.clone());
+        values.insert("files_app_dir".to_owned(), self.files_app_dir.clone());
+        values.insert("require_index_html".to_owned(), self.require_index_html.to_string());
+        values.insert("app_path".to_owned(), self.app_path.clone());
+        values.insert("api_path".to_owned(), self.api_path.clone());
+        if let Some(host) = &self.agent.host {
+            values.insert("agent.host".to_owned(), host.clone());
+        }
+        if let Some(port) = self.agent.port {
+            values.insert("agent.port".to_owned(), port.to_string());
+        }
+        if let Some(secure) = self.agent.secure {
+            values.insert("agent.secure".to_owned(), secure.to_string());
+        }
+        if !self.agent.end_point.is_empty() {
+            values.insert("agent.end_point".to_owned(), self.agent.end_point.clone());
+        }
+        for (id, path) in &self.end_points {
+            values.insert(format!("end_points.{}", id), path.clone());
+        }
+        if let Some(env_block) = environment_block(&self.yaml, &self.environment).as_hash() {
+            for (key, value) in env_block {
+                if let Some(key) = key.as_str() && !APP_CONFIG_SCHEMA.is_environment_field(key) {
+                    flatten_yaml(key, value, &mut values);
+                }
+            }
+        }
+        values
+    }
+
+    ///Fully resolved configuration of the running environment, in the layout of `config/core/app-config.yml`
+    /// (metadata plus an `environments:` block with this environment only). Defaults are written out and custom sections are kept,
+    /// so loading the result again gives the same configuration.
+    pub fn to_yaml(&self) -> Result<String, AnyErr> {
+        let agent = yaml_map(vec![
+            ("host", yaml_opt_str(&self.agent.host)),
+            ("port", self.agent.port.map(Yaml::Integer).unwrap_or(Yaml::BadValue)),
+            ("secure", self.agent.secure.map(Yaml::Boolean).unwrap_or(Yaml::BadValue)),
+            ("end_point", if self.agent.end_point.is_empty() { Yaml::BadValue } else { Yaml::String(self.agent.end_point.clone()) }),
+        ]);
+        let mut end_points: Vec<(&String, &String)> = self.end_points.iter().collect();
+        end_points.sort();
+        let end_points = end_points.into_iter()
+            .map(|(id, path)| yaml_map(vec![("id", Yaml::String(id.clone())), ("path", Yaml::String(path.clone()))]))
+            .collect();
+        let env_name = self.name_source == AppNameSource::EnvironmentConfig;
+        let mut env_block = yaml_map(vec![
+            ("app_name", if env_name { Yaml::String(self.name.clone()) } else { Yaml::BadValue }),
+            ("agent", if agent.as_hash().is_some_and(|h| h.is_empty()) { Yaml::BadValue } else { agent }),
+            ("files_app_dir", Yaml::String(self.files_app_dir.clone())),
+            ("require_index_html", if self.require_index_html { Yaml::Boolean(true) } else { Yaml::BadValue }),
+            ("app_path", Yaml::String(self.app_path.clone())),
+            ("api_path", Yaml::String(self.api_path.clone())),
+            ("end_points", if self.end_points.is_empty() { Yaml::BadValue } else { Yaml::Array(end_points) }),
+        ]);
+        if let (Some(block), Some(custom)) = (env_block.as_mut_hash(), environment_block(&self.yaml, &self.environment).as_hash()) {
+            for (key, value) in custom {
+                if key.as_str().is_some_and(|k| !APP_CONFIG_SCHEMA.is_environment_field(k)) {
+                    block.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        let mut app_config = yaml_map(vec![
+            (CONFIG_VERSION_KEY, Yaml::Integer(CURRENT_CONFIG_VERSION)),
+            //Only values read from the file: derived names and the application version are not written
+            ("app_name", self.yaml["app_name"].clone()),
+            ("version", self.yaml["version"].clone()),
+            ("environment", Yaml::String(self.environment.clone())),
+        ]);
+        //Top level custom keys (read by `get_value` as a fallback)
+        let environments = APP_CONFIG_SCHEMA.environments(&self.yaml);
+        if let (Some(root), Some(top_level)) = (app_config.as_mut_hash(), self.yaml.as_hash()) {
+            for (key, value) in top_level {
+                let name = yaml_key_to_string(key);
+                if name != ENVIRONMENTS_KEY && !root.contains_key(key) && !environments.contains(&name) {
+                    root.insert(key.clone(), value.clone());
+                }
+            }
+            root.insert(Yaml::String(ENVIRONMENTS_KEY.to_owned()), yaml_map(vec![(self.environment.as_str(), env_block)]));
+        }
+        emit_yaml(&app_config)
+    }
+
+    ///Deserializes a custom section (e.g. `database:`) into `T`.
+    /// The section is read from the running environment block, falling back to the top level of the file.
+    /// Errors include the key path of the offending value.
+    pub fn section<T: DeserializeOwned>(&self, section_name: &str) -> Result<T, AnyErr> {
+        match self.get_value(section_name) {
+            Some(section) => deserialize_yaml(section, section_name),
+            None => Err(format!("Section '{}' not found in environment '{}'.", section_name, self.environment).into()),
+        }
+    }
+
+    ///Raw access to any value by dotted key path (e.g. `agent.host`, `end_points.0.id`).
+    /// The running environment block is searched first, then the top level of the file.
+    /// Other environments are not reachable: the `environments:` root is skipped at the top level.
+    pub fn get_value(&self, key_path: &str) -> Option<&Yaml> {
+        let env_value = lookup_key_path(environment_block(&self.yaml, &self.environment), key_path);
+        let top_level = key_path.split('.').next() != Some(ENVIRONMENTS_KEY);
+        let value = if env_value.is_badvalue() && top_level { lookup_key_path(&self.yaml, key_path) } else { env_value };
+        if value.is_badvalue() { None } else { Some(value) }
+    }
+
+    ///String value at `key_path`. Ok(None) if the key does not exist, error if it is not a string.
+    pub fn get_str(&self, key_path: &str) -> Result<Option<String>, AnyErr> {
+        self.get_typed(key_path, "string", |v| v.as_str().map(|s| s.to_owned()))
+    }
+
+    ///Integer value at `key_path`. Ok(None) if the key does not exist, error if it is not an integer.
+    pub fn get_i64(&self, key_path: &str) -> Result<Option<i64>, AnyErr> {
+        self.get_typed(key_path, "integer", |v| v.as_i64())
+    }
+
+    ///Boolean value at `key_path`. Ok(None) if the key does not exist, error if it is not a boolean.
+    pub fn get_bool(&self, key_path: &str) -> Result<Option<bool>, AnyErr> {
+        self.get_typed(key_path, "boolean", |v| v.as_bool())
+    }
+
+    ///List of scalar values at `key_path` as strings. Ok(None) if the key does not exist, error if it is not a list of scalars.
+    pub fn get_list(&self, key_path: &str) -> Result<Option<Vec<String>>, AnyErr> {
+        self.get_typed(key_path, "list of scalars", |v| {
+            v.as_vec().and_then(|items| items.iter().map(yaml_scalar_to_string).collect())
+        })
+    }
+
+    fn get_typed<T>(&self, key_path: &str, expected: &str, convert: impl Fn(&Yaml) -> Option<T>) -> Result<Option<T>, AnyErr> {
+        match self.get_value(key_path) {
+            None | Some(Yaml::Null) => Ok(None),
+            Some(value) => match convert(value) {
+                Some(v) => Ok(Some(v)),
+                None => Err(format!("Invalid value at '{}' in environment '{}': expected {}, found {}.",
+                                    key_path, self.environment, expected, yaml_type_name(value)).into()),
+            },
+        }
+    }
+
     pub fn get_environment(&self) -> String {
         self.environment.clone()
     }
 
+    ///Rule that chose the running environment. Useful for logging.
+    pub fn get_environment_source(&self) -> &EnvironmentSource {
+        &self.environment_source
+    }
+
+    ///`files_app_dir` as written in the file. See `get_files_app_path` for the directory to serve.
     pub fn get_file_app_dir(&self) -> String {
         self.files_app_dir.clone()
     }
 
+    ///Static files directory resolved at load time. A relative `files_app_dir` is taken from the folder the
+    /// configuration was found in (working or executable directory for `config/core/app-config.yml`, the file folder otherwise),
+    /// or from the executable directory for embedded configurations. Canonical when the directory exists.
+    pub fn get_files_app_path(&self) -> &Path {
+        &self.files_app_path
+    }
+
+    ///True when the static files directory must contain an `index.html`
+    pub fn requires_index_html(&self) -> bool {
+        self.require_index_html
+    }
+
+    ///Checks the static files directory can be read and, with `require_index_html`, has an `index.html`.
+    /// Loading only checks it when `require_index_html` is set; call this to check it in any case (e.g. `bt-config check`).
+    pub fn check_files_app_path(&self) -> Result<(), AnyErr> {
+        check_files_app_dir(&self.files_app_path, self.require_index_html).map_err(|e| e.into())
+    }
+
     pub fn get_app_path(&self) -> String {
         self.app_path.clone()
     }
@@ -125,14 +424,25 @@ impl AppConfig {
             .to_string()
     }
 
+    ///Application name. See `resolve_app_name` for the resolution order.
     pub fn get_app_name(&self) -> &String {
         &self.name
     }
 
+    ///Rule that chose `get_app_name`. Useful for logging.
+    pub fn get_app_name_source(&self) -> &AppNameSource {
+        &self.name_source
+    }
+
     pub fn get_version(&self) -> &String {
         &self.version
     }
 
+    ///Top level `version:` of the file, if present and valid
+    pub fn get_file_version(&self) -> Option<Version> {
+        yaml_scalar_to_string(&self.yaml["version"]).and_then(|v| v.parse().ok())
+    }
+
     pub fn get_agent_url(&self) -> String{
         let mut agent_url = if self.agent.secure.is_some() {
                                     if self.agent.secure.unwrap() {"https://".to_owned()} else {"http://".to_owned()}
@@ -148,14 +458,174 @@ impl AppConfig {
 }
 
 
+///Base folder of a relative `files_app_dir`: the root searched for the default file (working or executable directory),
+/// the working directory for a relative path set in the env variable, the folder of a file found elsewhere,
+/// or the executable directory when there is no file.
+fn files_base_dir(location: Option<&ConfigLocation>) -> Option<PathBuf> {
+    match location {
+        Some(found) if matches!(found.location, SearchLocation::WorkingDir | SearchLocation::ExeDir) =>
+            found.path.ancestors().nth(Path::new(APP_YML_CONFIG).components().count()).map(|p| p.to_path_buf()),
+        Some(found) if matches!(found.location, SearchLocation::EnvVar(_)) && found.path.is_relative() => env::current_dir().ok(),
+        Some(found) => found.path.parent().map(|p| p.to_path_buf()),
+        None => env::current_exe().ok().and_then(|p| p.parent().map(|d| d.to_path_buf())),
+    }
+}
+
+fn resolve_files_app_dir(files_app_dir: &str, location: Option<&ConfigLocation>) -> PathBuf {
+    let dir = Path::new(files_app_dir);
+    let path = match files_base_dir(location) {
+        Some(base) if dir.is_relative() => base.join(dir),
+        _ => dir.to_path_buf(),
+    };
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+///Checks the static files directory can be listed and, if required, has an `index.html`.
+fn check_files_app_dir(path: &Path, require_index_html: bool) -> Result<(), String> {
+    if let Err(e) = fs::read_dir(path) {
+        return Err(format!("Static files directory '{}' cannot be read: {}", path.display(), e));
+    }
+    if require_index_html && !path.join("index.html").is_file() {
+        return Err(format!("Static files directory '{}' has no index.html.", path.display()));
+    }
+    Ok(())
+}
+
+///Checks the running application version against the file `min_app_version` and `max_app_version` (both inclusive).
+fn check_app_version(app_config: &Yaml, app_version: &str) -> Result<(), String> {
+    let bound = |key: &str| yaml_scalar_to_string(&app_config[key]).and_then(|v| v.parse::<Version>().ok());
+    let (min, max) = (bound("min_app_version"), bound("max_app_version"));
+    if min.is_none() && max.is_none() {
+        return Ok(());
+    }
+    let running = app_version.parse::<Version>().map_err(|e| format!("Cannot check app-config compatibility. {}", e))?;
+    let too_old = min.as_ref().is_some_and(|m| running.cmp_precedence(m).is_lt());
+    let too_new = max.as_ref().is_some_and(|m| running.cmp_precedence(m).is_gt());
+    if too_old || too_new {
+        let show = |v: Option<Version>| v.map(|v| v.to_string()).unwrap_or("any".to_owned());
+        return Err(format!("Application version {} is not compatible with app-config (min_app_version: {}, max_app_version: {}).",
+                           running, show(min), show(max)));
+    }
+    Ok(())
+}
+
+impl AppConfigBuilder {
+    ///Environment name. Defaults to `dev` in debug builds and `prod` in release builds.
+    pub fn environment(mut self, environment: &str) -> Self {
+        self.environment = Some(environment.to_owned());
+        self
+    }
+
+    pub fn app_name(mut self, app_name: &str) -> Self {
+        self.app_name = Some(app_name.to_owned());
+        self
+    }
+
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = Some(version.to_owned());
+        self
+    }
+
+    pub fn files_app_dir(mut self, files_app_dir: &str) -> Self {
+        self.files_app_dir = Some(files_app_dir.to_owned());
+        self
+    }
+
+    pub fn require_index_html(mut self, require_index_html: bool) -> Self {
+        self.require_index_html = Some(require_index_html);
+        self
+    }
+
+    pub fn app_path(mut self, app_path: &str) -> Self {
+        self.app_path = Some(app_path.to_owned());
+        self
+    }
+
+    pub fn api_path(mut self, api_path: &str) -> Self {
+        self.api_path = Some(api_path.to_owned());
+        self
+    }
+
+    pub fn agent_host(mut self, host: &str) -> Self {
+        self.agent_host = Some(host.to_owned());
+        self
+    }
+
+    pub fn agent_port(mut self, port: i64) -> Self {
+        self.agent_port = Some(port);
+        self
+    }
+
+    pub fn agent_secure(mut self, secure: bool) -> Self {
+        self.agent_secure = Some(secure);
+        self
+    }
+
+    pub fn agent_end_point(mut self, end_point: &str) -> Self {
+        self.agent_end_point = Some(end_point.to_owned());
+        self
+    }
+
+    ///Adds an end point. `path` None defaults to `/<id>`.
+    pub fn end_point(mut self, id: &str, path: Option<&str>) -> Self {
+        self.end_points.push((id.to_owned(), path.map(|p| p.to_owned())));
+        self
+    }
+
+    ///Validates and builds the configuration. Does not initialize the global App Base URL.
+    pub fn build(self) -> Result<AppConfig, AnyErr> {
+        let resolved = match &self.environment {
+            Some(environment) => ResolvedEnvironment::explicit(environment),
+            None => ResolvedEnvironment{ name: DEFAULT_RUN_ENV.to_owned(), source: EnvironmentSource::BuildDefault },
+        };
+        let environment = resolved.name.clone();
+        let agent = yaml_map(vec![
+            ("host", yaml_opt_str(&self.agent_host)),
+            ("port", self.agent_port.map(Yaml::Integer).unwrap_or(Yaml::BadValue)),
+            ("secure", self.agent_secure.map(Yaml::Boolean).unwrap_or(Yaml::BadValue)),
+            ("end_point", yaml_opt_str(&self.agent_end_point)),
+        ]);
+        let end_points = self.end_points.iter()
+            .map(|(id, path)| yaml_map(vec![("id", Yaml::String(id.clone())), ("path", yaml_opt_str(path))]))
+            .collect();
+        let env_block = yaml_map(vec![
+            ("agent", if agent.as_hash().is_some_and(|h| h.is_empty()) { Yaml::BadValue } else { agent }),
+            ("files_app_dir", yaml_opt_str(&self.files_app_dir)),
+            ("require_index_html", self.require_index_html.map(Yaml::Boolean).unwrap_or(Yaml::BadValue)),
+            ("app_path", yaml_opt_str(&self.app_path)),
+            ("api_path", yaml_opt_str(&self.api_path)),
+            ("end_points", if self.end_points.is_empty() { Yaml::BadValue } else { Yaml::Array(end_points) }),
+        ]);
+        let app_config = yaml_map(vec![
+            (CONFIG_VERSION_KEY, Yaml::Integer(CURRENT_CONFIG_VERSION)),
+            ("app_name", yaml_opt_str(&self.app_name)),
+            (ENVIRONMENTS_KEY, yaml_map(vec![(environment.as_str(), env_block)])),
+        ]);
+
+        let app_info = AppInfo::new(CargoPkg::default(), UNKNOWN_APP_NAME, UNKNOWN_APP_VERSION, "", "");
+        let mut ac = AppConfig::from_yaml(app_config, &resolved, &app_info, None, ValidationMode::Lenient, None)?;
+        if let Some(version) = self.version {
+            ac.version = version;
+        }
+        Ok(ac)
+    }
+}
+
+
 //***********/
 // UNIT TEST 
 //***********/
 #[cfg(test)]
+#[allow(deprecated)]
 mod app_config_tests {
     use bt_logger::{build_logger, LogLevel, LogTarget};
+    use yaml_rust2::YamlLoader;
 
-    use crate::app_info::AppInfo;
+    use crate::app_info::{AppInfo, CargoPkg, UNKNOWN_APP_VERSION};
+    use crate::app_name::AppNameSource;
+    use crate::config_file::{ConfigLocation, SearchLocation};
+    use crate::environment::{EnvironmentCandidates, EnvironmentSource, ResolvedEnvironment};
+    use crate::schema::{SchemaErrors, ValidationMode};
 
     use super::AppConfig;
 
@@ -199,7 +669,7 @@ mod app_config_tests {
         assert_eq!(ac.app_path,"/app");
         assert_eq!(ac.api_path,"/none/api/");
         assert_eq!(ac.get_environment(),"devNone");
-        assert_eq!(ac.get_version(),"0.6.0");
+        assert_eq!(ac.get_version(),"0.7.0");
     }
 
     #[test]
@@ -214,7 +684,7 @@ mod app_config_tests {
         assert_eq!(ac.app_path,"/app");
         assert_eq!(ac.api_path,"/none/api/");
         assert_eq!(ac.get_environment(),"devNone");
-        assert_eq!(ac.get_version(),"0.6.0");
+        assert_eq!(ac.get_version(),"0.7.0");
     }
 
     #[test]
@@ -229,7 +699,7 @@ mod app_config_tests {
         assert_eq!(ac.app_path,"/app");
         assert_eq!(ac.api_path,"/api");
         assert_eq!(ac.get_environment(),er);
-        assert_eq!(ac.get_version(),"0.6.0");
+        assert_eq!(ac.get_version(),"0.7.0");
     }    
 
     #[test]
@@ -245,7 +715,7 @@ mod app_config_tests {
         assert_eq!(ac.get_app_path(),"/jeremy");
         assert_eq!(ac.get_api_path(),"/ai/api/");
         assert_eq!(ac.get_environment(),er);
-        assert_eq!(ac.get_version(),"0.6.0");
+        assert_eq!(ac.get_version(),"0.7.0");
     }
 
    #[test]
@@ -262,9 +732,295 @@ mod app_config_tests {
         assert_eq!(ac.get_app_path(),"/embeded");
         assert_eq!(ac.get_api_path(),"/ai/api/");
         assert_eq!(ac.get_environment(),er);
-        assert_eq!(ac.get_version(),"0.6.0");
+        assert_eq!(ac.get_version(),"0.7.0");
     }    
 
+    #[derive(Debug, serde::Deserialize)]
+    struct DatabaseSection{
+        url: String,
+        pool_size: u32,
+    }
+
+    #[test]
+    pub fn test_section_success(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        const YML_CONTENT: &str = "environments:\n  dev:\n    database:\n      url: postgres://localhost/bt\n      pool_size: 5\n";
+        let ac = AppConfig::new("dev", &app_info, Some(YML_CONTENT)).unwrap();
+        let db: DatabaseSection = ac.section("database").unwrap();
+        assert_eq!(db.url,"postgres://localhost/bt");
+        assert_eq!(db.pool_size,5);
+        assert_eq!(ac.effective_values()["database.pool_size"],"5");
+    }
+
+    #[test]
+    pub fn test_section_errors(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        const YML_CONTENT: &str = "dev:\n  database:\n    url: x\n    pool_size: big\n";
+        let ac = AppConfig::new("dev", &app_info, Some(YML_CONTENT)).unwrap();
+        let err = ac.section::<DatabaseSection>("database").unwrap_err();
+        assert!(err.to_string().contains("'database.pool_size'"));
+        assert!(ac.section::<DatabaseSection>("cache").is_err());
+    }
+
+    #[test]
+    pub fn test_get_value_success(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        let ac = AppConfig::new("jeremy_dev", &app_info, None).unwrap();
+        assert_eq!(ac.get_value("agent.host").and_then(|v| v.as_str()),Some("localhost"));
+        assert_eq!(ac.get_str("agent.host").unwrap(),Some("localhost".to_owned()));
+        assert_eq!(ac.get_i64("agent.port").unwrap(),Some(23332));
+        assert_eq!(ac.get_bool("agent.secure").unwrap(),Some(false));
+        assert_eq!(ac.get_str("end_points.1.id").unwrap(),Some("prompt".to_owned()));
+        //Fallback to top level keys
+        assert_eq!(ac.get_str("app_name").unwrap(),Some("BACHUETECH AI".to_owned()));
+        assert_eq!(ac.get_str("agent.unknown").unwrap(),None);
+        assert!(ac.get_value("unknown").is_none());
+        //Other environments are not reachable
+        assert!(ac.get_value("environments.embed_dev.agent.port").is_none());
+        assert!(ac.get_value("environments").is_none());
+    }
+
+    #[test]
+    pub fn test_get_value_type_errors(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        const YML_CONTENT: &str = "dev:\n  features: [chat, 2, true]\n  nested: [[a]]\n  port: abc\n";
+        let ac = AppConfig::new("dev", &app_info, Some(YML_CONTENT)).unwrap();
+        assert_eq!(ac.get_list("features").unwrap(),Some(vec!["chat".to_owned(),"2".to_owned(),"true".to_owned()]));
+        assert!(ac.get_list("nested").is_err());
+        assert!(ac.get_i64("port").is_err());
+        assert!(ac.get_bool("features").is_err());
+    }
+
+    #[test]
+    pub fn test_app_config_schema_violations(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        const YML_CONTENT: &str = "dev:\n  api_path: api\n  agent:\n    port: 99999\n  end_points:\n    - path: /chat\n";
+        let err = AppConfig::new("dev", &app_info, Some(YML_CONTENT)).unwrap_err();
+        let errors = err.downcast_ref::<SchemaErrors>().unwrap();
+        assert_eq!(errors.violations.len(),3);
+        assert!(AppConfig::json_schema().contains("\"end_points\""));
+    }
+
+    #[test]
+    pub fn test_app_config_unknown_keys(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        const YML_CONTENT: &str = "dev:\n  api_pth: /ai/api/\n  end_point:\n    - id: chat\n";
+        let ac = AppConfig::new("dev", &app_info, Some(YML_CONTENT)).unwrap();
+        assert_eq!(ac.get_api_path(),"/api");
+        let err = AppConfig::new_with_validation("dev", &app_info, Some(YML_CONTENT), ValidationMode::Strict).unwrap_err();
+        assert!(err.to_string().contains("did you mean 'api_path'?"));
+        assert!(err.to_string().contains("did you mean 'end_points'?"));
+    }
+
+    #[test]
+    pub fn test_effective_values(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        let values = AppConfig::new("jeremy_dev", &app_info, None).unwrap().effective_values();
+        assert_eq!(values["agent.port"],"23332");
+        assert_eq!(values["end_points.models"],"/models");
+        assert_eq!(values["files_app_dir"],"site");
+        let values = AppConfig::new("empty", &app_info, None).unwrap().effective_values();
+        assert!(!values.contains_key("agent.host"));
+        assert_eq!(values["api_path"],"/api");
+    }
+
+    #[test]
+    pub fn test_list_environments(){
+        let envs = AppConfig::list_environments(None).unwrap();
+        let names: Vec<&str> = envs.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["empty", "dev", "devNone", "jeremy_dev", "embed_dev"]);
+        assert!(envs[0].is_empty);
+        assert!(!envs[1].is_empty);
+    }
+
+    #[test]
+    pub fn test_builder_success(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let ac = AppConfig::builder()
+            .environment("unit")
+            .app_name("Builder App")
+            .version("1.0.0")
+            .app_path("/builder")
+            .agent_host("localhost")
+            .agent_port(8080)
+            .agent_secure(false)
+            .agent_end_point("/chat")
+            .end_point("models", None)
+            .build().unwrap();
+        assert_eq!(ac.get_environment(),"unit");
+        assert_eq!(ac.get_app_name(),"Builder App");
+        assert_eq!(ac.get_version(),"1.0.0");
+        assert_eq!(ac.get_app_path(),"/builder");
+        assert_eq!(ac.get_api_path(),"/api");
+        assert_eq!(ac.get_file_app_dir(),"site");
+        assert_eq!(ac.get_agent_url(),"http://localhost:8080/chat");
+        assert_eq!(ac.get_end_point("models"),"/models");
+    }
+
+    #[test]
+    pub fn test_builder_validation(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let err = AppConfig::builder().api_path("api").agent_port(70000).end_point("chat", Some("chat")).build().unwrap_err();
+        let errors = err.downcast_ref::<SchemaErrors>().unwrap();
+        assert_eq!(errors.violations.len(),3);
+    }
+
+    #[test]
+    pub fn test_to_yaml_round_trip(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        let ac = AppConfig::new("jeremy_dev", &app_info, None).unwrap();
+        let yml = ac.to_yaml().unwrap();
+        assert!(yml.contains("environments:\n  jeremy_dev:\n"));
+        assert!(yml.contains("port: 23332"));
+        let reloaded = AppConfig::new("jeremy_dev", &app_info, Some(&yml)).unwrap();
+        assert_eq!(reloaded.effective_values(), ac.effective_values());
+        assert_eq!(reloaded.to_yaml().unwrap(), yml);
+
+        let built = AppConfig::builder().environment("unit").app_name("Builder App").end_point("models", None).build().unwrap();
+        let reloaded = AppConfig::new("unit", &app_info, Some(&built.to_yaml().unwrap())).unwrap();
+        assert_eq!(reloaded.get_end_point("models"),"/models");
+        assert_eq!(reloaded.get_app_name(),"Builder App");
+        assert_eq!(reloaded.get_file_app_dir(),"site");
+    }
+
+    #[test]
+    pub fn test_to_yaml_keeps_file_metadata(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::new(CargoPkg{ pkg_name: Some("bt-ai"), pkg_version: Some("1.0.0"), ..Default::default() }, "AppName", "0.0.0", "", "");
+        let ac = AppConfig::new("qa", &app_info, Some("version: v0.1.0\nenvironments:\n  qa:\n")).unwrap();
+        let yml = ac.to_yaml().unwrap();
+        assert!(!yml.contains("app_name"));
+        let reloaded = AppConfig::new("qa", &app_info, Some(&yml)).unwrap();
+        assert_eq!(reloaded.get_file_version().unwrap().to_string(),"0.1.0");
+        assert_eq!(reloaded.get_app_name_source(),&AppNameSource::PackageName);
+        let yml = AppConfig::new("qa", &app_info, Some("environments:\n  qa:\n")).unwrap().to_yaml().unwrap();
+        assert!(AppConfig::new("qa", &app_info, Some(&yml)).unwrap().get_file_version().is_none());
+    }
+
+    #[test]
+    pub fn test_new_layered(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        //The default file (config/core/app-config.yml) is merged over the base
+        const BASE_CONTENT: &str = "environments:\n  base_only:\n    app_path: /base\n  jeremy_dev:\n    api_path: /base/api\n    cache:\n      ttl: 60\n";
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        let ac = AppConfig::new_layered("jeremy_dev", &app_info, BASE_CONTENT, ValidationMode::Lenient).unwrap();
+        assert_eq!(ac.get_app_path(),"/jeremy");
+        assert_eq!(ac.get_api_path(),"/ai/api/");
+        assert_eq!(ac.get_i64("cache.ttl").unwrap(),Some(60));
+        assert_eq!(ac.get_i64("agent.port").unwrap(),Some(23332));
+        assert_eq!(ac.get_app_name(),"BACHUETECH AI");
+        assert_eq!(ac.get_config_location().unwrap().location, SearchLocation::WorkingDir);
+        let ac = AppConfig::new_layered("base_only", &app_info, BASE_CONTENT, ValidationMode::Lenient).unwrap();
+        assert_eq!(ac.get_app_path(),"/base");
+    }
+
+    #[test]
+    pub fn test_app_version_compatibility(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::new(CargoPkg{ pkg_version: Some("0.6.0"), ..Default::default() }, "AppName", "0.0.0", "", "");
+        const OLD_APP: &str = "version: v0.1.0\nmin_app_version: 0.7\nqa:\n";
+        let ac = AppConfig::new("qa", &app_info, Some(OLD_APP)).unwrap();
+        assert_eq!(ac.get_file_version().unwrap().to_string(),"0.1.0");
+        let err = AppConfig::new_with_validation("qa", &app_info, Some(OLD_APP), ValidationMode::Strict).unwrap_err();
+        assert!(err.to_string().contains("Application version 0.6.0 is not compatible with app-config (min_app_version: 0.7.0, max_app_version: any)"));
+        const IN_RANGE: &str = "min_app_version: v0.6.0\nmax_app_version: 0.6\nqa:\n";
+        assert!(AppConfig::new_with_validation("qa", &app_info, Some(IN_RANGE), ValidationMode::Strict).is_ok());
+        let err = AppConfig::new("qa", &app_info, Some("max_app_version: latest\nqa:\n")).unwrap_err();
+        assert!(err.to_string().contains("max_app_version: Invalid version 'latest'"));
+        //Numeric file version
+        let ac = AppConfig::new_with_validation("qa", &app_info, Some("version: 1.0\nqa:\n"), ValidationMode::Strict).unwrap();
+        assert_eq!(ac.get_file_version().unwrap().to_string(),"1.0.0");
+        //Version 0.0.0 is checked like any other
+        let zero = AppInfo::new(CargoPkg::default(), "AppName", UNKNOWN_APP_VERSION, "", "");
+        assert!(AppConfig::new_with_validation("qa", &zero, Some(OLD_APP), ValidationMode::Strict).is_err());
+        //No application version: the check is skipped
+        let dir = std::env::temp_dir().join(format!("bt_core_config_app_version_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("app-config.yml"), OLD_APP).unwrap();
+        assert!(AppConfig::from_file("qa", &app_info, None, &dir.join("app-config.yml"), ValidationMode::Strict).is_ok());
+        assert!(AppConfig::from_file("qa", &app_info, Some("0.6.0"), &dir.join("app-config.yml"), ValidationMode::Strict).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    pub fn test_files_app_path(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let dir = std::env::temp_dir().join(format!("bt_core_config_files_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("config/core")).unwrap();
+        std::fs::create_dir_all(dir.join("site")).unwrap();
+        let app_info = AppInfo::new(CargoPkg::default(), "AppName", "1.0.0", "", "");
+        const REQUIRED: &str = "environments:\n  qa:\n    require_index_html: true\n";
+        let in_cwd = ConfigLocation{ path: dir.join("config/core/app-config.yml"), location: SearchLocation::WorkingDir };
+        let yaml = || YamlLoader::load_from_str(REQUIRED).unwrap().remove(0);
+
+        let err = AppConfig::from_yaml(yaml(), &ResolvedEnvironment::explicit("qa"), &app_info, Some(app_info.version), ValidationMode::Strict, Some(in_cwd.clone())).unwrap_err();
+        assert!(err.to_string().contains("has no index.html"));
+        let ac = AppConfig::from_yaml(yaml(), &ResolvedEnvironment::explicit("qa"), &app_info, Some(app_info.version), ValidationMode::Lenient, Some(in_cwd.clone())).unwrap();
+        assert_eq!(ac.get_files_app_path(), dir.join("site").canonicalize().unwrap());
+
+        std::fs::write(dir.join("site/index.html"), "<html></html>").unwrap();
+        let ac = AppConfig::from_yaml(yaml(), &ResolvedEnvironment::explicit("qa"), &app_info, Some(app_info.version), ValidationMode::Strict, Some(in_cwd.clone())).unwrap();
+        assert!(ac.requires_index_html());
+        assert!(ac.check_files_app_path().is_ok());
+        //Without require_index_html a missing directory is only reported on request
+        let missing = YamlLoader::load_from_str("environments:\n  qa:\n    files_app_dir: missing\n").unwrap().remove(0);
+        let ac = AppConfig::from_yaml(missing, &ResolvedEnvironment::explicit("qa"), &app_info, Some(app_info.version), ValidationMode::Strict, Some(in_cwd)).unwrap();
+        assert!(ac.check_files_app_path().unwrap_err().to_string().contains("cannot be read"));
+        //Files found outside the search roots resolve against their own folder
+        let in_env = ConfigLocation{ path: dir.join("app-config.yml"), location: SearchLocation::EnvVar("BT_APP_CONFIGYMLFILE".to_owned()) };
+        let ac = AppConfig::from_yaml(yaml(), &ResolvedEnvironment::explicit("qa"), &app_info, Some(app_info.version), ValidationMode::Strict, Some(in_env)).unwrap();
+        assert_eq!(ac.get_files_app_path(), dir.join("site").canonicalize().unwrap());
+        //A relative path in the env variable keeps the working directory as base
+        let relative_env = ConfigLocation{ path: std::path::PathBuf::from("app-config.yml"), location: SearchLocation::EnvVar("BT_APP_CONFIGYMLFILE".to_owned()) };
+        assert_eq!(super::files_base_dir(Some(&relative_env)), std::env::current_dir().ok());
+        //Explicit file
+        std::fs::write(dir.join("app-config.yml"), REQUIRED).unwrap();
+        let ac = AppConfig::from_file("qa", &app_info, Some(app_info.version), &dir.join("app-config.yml"), ValidationMode::Strict).unwrap();
+        assert_eq!(ac.get_files_app_path(), dir.join("site").canonicalize().unwrap());
+        assert_eq!(ac.get_config_location().unwrap().location, SearchLocation::Explicit);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    pub fn test_environment_app_name(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::new(CargoPkg{ pkg_name: Some("bt-ai"), ..Default::default() }, "AppName", "1.0.0", "", "");
+        const NAMES: &str = "app_name: BACHUETECH AI\nenvironments:\n  staging:\n    app_name: BACHUETECH AI (staging)\n  prod:\n";
+        let ac = AppConfig::new("staging", &app_info, Some(NAMES)).unwrap();
+        assert_eq!(ac.get_app_name(),"BACHUETECH AI (staging)");
+        assert_eq!(ac.get_app_name_source(),&AppNameSource::EnvironmentConfig);
+        let yml = ac.to_yaml().unwrap();
+        assert!(yml.contains("app_name: BACHUETECH AI\n"));
+        let reloaded = AppConfig::new("staging", &app_info, Some(&yml)).unwrap();
+        assert_eq!(reloaded.get_app_name(),"BACHUETECH AI (staging)");
+        let ac = AppConfig::new("prod", &app_info, Some(NAMES)).unwrap();
+        assert_eq!(ac.get_app_name(),"BACHUETECH AI");
+        assert_eq!(ac.get_app_name_source(),&AppNameSource::ConfigFile);
+    }
+
+    #[test]
+    pub fn test_environment_source(){
+        build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::new(CargoPkg::default(), "AppName", "1.0.0", "", "");
+        const ENVS: &str = "environment: qa\nenvironments:\n  qa:\n  prod:\n";
+        let ac = AppConfig::new("prod", &app_info, Some(ENVS)).unwrap();
+        assert_eq!(ac.get_environment_source(),&EnvironmentSource::Explicit);
+        let ac = AppConfig::new("", &app_info, Some(ENVS)).unwrap();
+        assert_eq!((ac.get_environment().as_str(), ac.get_environment_source()),("qa", &EnvironmentSource::ConfigFile));
+        let candidates = EnvironmentCandidates{ command_line: Some("prod".to_owned()), ..EnvironmentCandidates::explicit("") };
+        let ac = AppConfig::new_with_candidates(&candidates, &app_info, Some(ENVS), ValidationMode::Lenient).unwrap();
+        assert_eq!((ac.get_environment().as_str(), ac.get_environment_source()),("prod", &EnvironmentSource::CommandLine));
+        assert_eq!(AppConfig::builder().build().unwrap().get_environment_source(),&EnvironmentSource::BuildDefault);
+    }
+
     #[test]
     pub fn test_end_points(){
         build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
@@ -0,0 +1,248 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::env;
+
+use bt_any_error::any_err::AnyErr;
+use bt_logger::log_info;
+
+use crate::app_info::AppInfo;
+use crate::app_name::app_dir_name;
+
+///Where a directory path came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirSource {
+    ///Set by the application in `AppDirsOverrides`
+    Override,
+    ///XDG environment variable (e.g. `XDG_DATA_HOME`)
+    EnvVar(&'static str),
+    ///XDG default under the home directory (e.g. `~/.local/share`)
+    HomeDefault,
+    ///Folder of the running executable. Used when no home directory is known and on non-Unix platforms.
+    ExeDir,
+    ///System temporary directory (runtime directory without `XDG_RUNTIME_DIR`)
+    TempDir,
+}
+
+impl fmt::Display for DirSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DirSource::Override => write!(f, "override"),
+            DirSource::EnvVar(name) => write!(f, "env variable {}", name),
+            DirSource::HomeDefault => write!(f, "home directory default"),
+            DirSource::ExeDir => write!(f, "executable directory"),
+            DirSource::TempDir => write!(f, "temporary directory"),
+        }
+    }
+}
+
+///A resolved directory and the source of its path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppDir {
+    pub path: PathBuf,
+    pub source: DirSource,
+}
+
+///Directories to use instead of the XDG locations. Paths are used as is (the application name is not appended).
+#[derive(Debug, Clone, Default)]
+pub struct AppDirsOverrides {
+    pub config: Option<PathBuf>,
+    pub data: Option<PathBuf>,
+    pub cache: Option<PathBuf>,
+    pub log: Option<PathBuf>,
+    pub runtime: Option<PathBuf>,
+}
+
+///Per-application directories. On Unix they follow the XDG base directory spec:
+/// config `$XDG_CONFIG_HOME/<app>` (`~/.config/<app>`), data `$XDG_DATA_HOME/<app>` (`~/.local/share/<app>`),
+/// cache `$XDG_CACHE_HOME/<app>` (`~/.cache/<app>`), log `$XDG_STATE_HOME/<app>/log` (`~/.local/state/<app>/log`)
+/// and runtime `$XDG_RUNTIME_DIR/<app>` (`<temp dir>/<app>`). Elsewhere they are folders next to the executable.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppDirs {
+    pub config: AppDir,
+    pub data: AppDir,
+    pub cache: AppDir,
+    pub log: AppDir,
+    pub runtime: AppDir,
+}
+
+struct DirRule {
+    xdg_var: &'static str,
+    home_default: &'static str,
+    suffix: Option<&'static str>,
+    exe_folder: &'static str,
+}
+
+const CONFIG_RULE: DirRule = DirRule{ xdg_var: "XDG_CONFIG_HOME", home_default: ".config", suffix: None, exe_folder: "config" };
+const DATA_RULE: DirRule = DirRule{ xdg_var: "XDG_DATA_HOME", home_default: ".local/share", suffix: None, exe_folder: "data" };
+const CACHE_RULE: DirRule = DirRule{ xdg_var: "XDG_CACHE_HOME", home_default: ".cache", suffix: None, exe_folder: "cache" };
+const LOG_RULE: DirRule = DirRule{ xdg_var: "XDG_STATE_HOME", home_default: ".local/state", suffix: Some("log"), exe_folder: "log" };
+
+impl AppDirs {
+    ///Resolves the directories of `app_dir_name(Some(app_info))` from the process environment. The config directory is
+    /// the `XdgConfigHome` folder searched for configuration files.
+    pub fn resolve(app_info: &AppInfo) -> Self {
+        Self::resolve_with(app_info, &AppDirsOverrides::default())
+    }
+
+    ///Same as `resolve`, with some directories set by the application
+    pub fn resolve_with(app_info: &AppInfo, overrides: &AppDirsOverrides) -> Self {
+        let exe_dir = env::current_exe().ok().and_then(|p| p.parent().map(|d| d.to_path_buf()));
+        let env = DirEnv{ var: &|name| env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from), home: env::home_dir(),
+                          exe_dir, temp_dir: env::temp_dir(), xdg: cfg!(unix) };
+        resolve_dirs(&app_dir_name(Some(app_info)), overrides, &env)
+    }
+
+    ///Creates every directory. Config and runtime directories are private to the user (0700 on Unix), the others 0755.
+    pub fn create_all(&self) -> Result<(), AnyErr> {
+        for (dir, private) in [(&self.config, true), (&self.data, false), (&self.cache, false), (&self.log, false), (&self.runtime, true)] {
+            create_dir(&dir.path, private)?;
+        }
+        Ok(())
+    }
+}
+
+fn create_dir(path: &Path, private: bool) -> Result<(), AnyErr> {
+    if path.is_dir() {
+        return Ok(());
+    }
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(if private { 0o700 } else { 0o755 });
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    builder.create(path).map_err(|e| format!("Cannot create '{}': {}", path.display(), e))?;
+    log_info!("", "Created directory '{}'.", path.display());
+    Ok(())
+}
+
+///Process environment used to resolve the directories
+struct DirEnv<'a> {
+    var: &'a dyn Fn(&str) -> Option<PathBuf>,
+    home: Option<PathBuf>,
+    exe_dir: Option<PathBuf>,
+    temp_dir: PathBuf,
+    xdg: bool,
+}
+
+fn resolve_dirs(app_name: &str, overrides: &AppDirsOverrides, env: &DirEnv) -> AppDirs {
+    let runtime = match &overrides.runtime {
+        Some(path) => AppDir{ path: path.clone(), source: DirSource::Override },
+        None => match (env.xdg, (env.var)("XDG_RUNTIME_DIR")) {
+            (true, Some(root)) => AppDir{ path: root.join(app_name), source: DirSource::EnvVar("XDG_RUNTIME_DIR") },
+            (true, None) => AppDir{ path: env.temp_dir.join(app_name), source: DirSource::TempDir },
+            (false, _) => exe_dir(app_name, "run", env),
+        },
+    };
+    AppDirs {
+        config: resolve_dir(app_name, &overrides.config, &CONFIG_RULE, env),
+        data: resolve_dir(app_name, &overrides.data, &DATA_RULE, env),
+        cache: resolve_dir(app_name, &overrides.cache, &CACHE_RULE, env),
+        log: resolve_dir(app_name, &overrides.log, &LOG_RULE, env),
+        runtime,
+    }
+}
+
+fn resolve_dir(app_name: &str, override_path: &Option<PathBuf>, rule: &DirRule, env: &DirEnv) -> AppDir {
+    if let Some(path) = override_path {
+        return AppDir{ path: path.clone(), source: DirSource::Override };
+    }
+    if !env.xdg {
+        return exe_dir(app_name, rule.exe_folder, env);
+    }
+    let (root, source) = match ((env.var)(rule.xdg_var), &env.home) {
+        (Some(root), _) if root.is_absolute() => (root, DirSource::EnvVar(rule.xdg_var)),
+        (_, Some(home)) => (home.join(rule.home_default), DirSource::HomeDefault),
+        (_, None) => return exe_dir(app_name, rule.exe_folder, env),
+    };
+    let path = root.join(app_name);
+    AppDir{ path: rule.suffix.map(|s| path.join(s)).unwrap_or(path), source }
+}
+
+fn exe_dir(app_name: &str, folder: &str, env: &DirEnv) -> AppDir {
+    match &env.exe_dir {
+        Some(dir) => AppDir{ path: dir.join(folder), source: DirSource::ExeDir },
+        None => AppDir{ path: env.temp_dir.join(app_name).join(folder), source: DirSource::TempDir },
+    }
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod app_dirs_tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::app_info::{AppInfo, CargoPkg};
+    use crate::app_name::app_dir_name;
+    use crate::config_file::{search_paths, SearchLocation};
+
+    use super::{resolve_dirs, AppDirs, AppDirsOverrides, DirEnv, DirSource};
+
+    fn test_env<'a>(var: &'a dyn Fn(&str) -> Option<PathBuf>, xdg: bool) -> DirEnv<'a> {
+        DirEnv{ var, home: Some(PathBuf::from("/home/ann")), exe_dir: Some(PathBuf::from("/opt/svc/bin")), temp_dir: PathBuf::from("/tmp"), xdg }
+    }
+
+    #[test]
+    pub fn test_resolve_xdg(){
+        let var = |name: &str| match name {
+            "XDG_DATA_HOME" => Some(PathBuf::from("/srv/data")),
+            "XDG_CACHE_HOME" => Some(PathBuf::from("relative/cache")),
+            _ => None,
+        };
+        let dirs = resolve_dirs("svc", &AppDirsOverrides::default(), &test_env(&var, true));
+        assert_eq!(dirs.config.path, Path::new("/home/ann/.config/svc"));
+        assert_eq!(dirs.config.source, DirSource::HomeDefault);
+        assert_eq!(dirs.data.path, Path::new("/srv/data/svc"));
+        assert_eq!(dirs.data.source, DirSource::EnvVar("XDG_DATA_HOME"));
+        //Relative XDG paths are invalid and ignored
+        assert_eq!(dirs.cache.path, Path::new("/home/ann/.cache/svc"));
+        assert_eq!(dirs.log.path, Path::new("/home/ann/.local/state/svc/log"));
+        assert_eq!(dirs.runtime.path, Path::new("/tmp/svc"));
+        assert_eq!(dirs.runtime.source, DirSource::TempDir);
+    }
+
+    #[test]
+    pub fn test_resolve_overrides_and_exe_dir(){
+        let overrides = AppDirsOverrides{ log: Some(PathBuf::from("/var/log/svc")), ..Default::default() };
+        let dirs = resolve_dirs("svc", &overrides, &test_env(&|_| None, false));
+        assert_eq!(dirs.log.path, Path::new("/var/log/svc"));
+        assert_eq!(dirs.log.source, DirSource::Override);
+        assert_eq!(dirs.data.path, Path::new("/opt/svc/bin/data"));
+        assert_eq!(dirs.data.source, DirSource::ExeDir);
+        assert_eq!(dirs.runtime.path, Path::new("/opt/svc/bin/run"));
+    }
+
+    #[test]
+    pub fn test_config_dir_is_searched(){
+        //Package name differs from the binary name: the config directory is still a searched folder
+        let app_info = AppInfo::new(CargoPkg{ pkg_name: Some("bt-ai"), ..Default::default() }, "bt-ai", "1.0.0", "", "");
+        let dirs = AppDirs::resolve(&app_info);
+        let searched = search_paths("config/core/app-config.yml", &app_dir_name(None));
+        let xdg = searched.iter().find(|c| c.location == SearchLocation::XdgConfigHome).unwrap();
+        assert_eq!(dirs.config.path.file_name(), xdg.path.parent().unwrap().file_name());
+        assert_ne!(dirs.config.path.file_name().unwrap(), "bt-ai");
+    }
+
+    #[test]
+    pub fn test_create_all(){
+        let root = std::env::temp_dir().join(format!("bt_core_config_dirs_{}", std::process::id()));
+        let overrides = AppDirsOverrides{ config: Some(root.join("config")), data: Some(root.join("data")), cache: Some(root.join("cache")),
+                                          log: Some(root.join("log")), runtime: Some(root.join("run")) };
+        let app_info = AppInfo::new(CargoPkg::default(), "svc", "1.0.0", "", "");
+        let dirs = AppDirs::resolve_with(&app_info, &overrides);
+        dirs.create_all().unwrap();
+        assert!(dirs.log.path.is_dir());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&dirs.runtime.path).unwrap().permissions().mode() & 0o777, 0o700);
+        }
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,22 +1,120 @@
 use std::{env, path::PathBuf};
 
+use serde_json::{json, Value};
+
+use crate::app_dirs::AppDirs;
+use crate::runtime_info::{self, RuntimeInfo};
+use crate::version::Version;
+
+///Name used when neither Cargo nor the executable provide one
+pub const UNKNOWN_APP_NAME: &str = "BT_UnkownApp";
+///Version used when Cargo does not provide one
+pub const UNKNOWN_APP_VERSION: &str = "0.0.0";
+
 ///Initialize basic information gathering data from Cargo.toml or default values.
+#[derive(Clone, Debug)]
 pub struct AppInfo{
     pub package_name: &'static str,
     pub version: &'static str,
     pub authors: &'static str,
     pub description: &'static str,
+    pub repository: &'static str,
+    pub homepage: &'static str,
+    pub license: &'static str,
+    ///Name of the binary target (`CARGO_BIN_NAME`). None for libraries and tests.
+    pub bin_name: Option<&'static str>,
+    ///Name shown to users (e.g. `BACHUETECH AI`). None to show `package_name`. See `with_display_name`.
+    pub display_name: Option<&'static str>,
+    ///Build provenance. Empty unless the build script calls `bt_core_config::build::emit_build_info()`.
+    pub build: BuildInfo,
 
     pub pkg_full_folder_path: Option<String>,
     pub pkg_root_folder_only: String,
 }
 
+///Cargo metadata given to `AppInfo::new`. Prefer `cargo_pkg!()`, which fills every field from the calling crate.
+/// Struct literals should end with `..Default::default()` so they keep compiling when fields are added:
+/// `CargoPkg{ pkg_name: option_env!("CARGO_PKG_NAME"), pkg_version: option_env!("CARGO_PKG_VERSION"), ..Default::default() }`.
+#[derive(Clone, Debug, Default)]
 pub struct CargoPkg{
     pub pkg_name: Option<&'static str>,
     pub pkg_version: Option<&'static str>,
     pub pkg_authors: Option<&'static str>,
     pub pkg_desc: Option<&'static str>,
+    pub pkg_repository: Option<&'static str>,
+    pub pkg_homepage: Option<&'static str>,
+    pub pkg_license: Option<&'static str>,
+    pub bin_name: Option<&'static str>,
+}
+
+///Serializes the `to_json` document without environment
+impl serde::Serialize for AppInfo {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_json(None).serialize(serializer)
+    }
+}
 
+///Build provenance captured at compile time by `bt_core_config::build::emit_build_info()`. Each value is None when unknown.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BuildInfo {
+    pub git_commit: Option<&'static str>,
+    ///Uncommitted changes in the working tree at build time
+    pub git_dirty: Option<bool>,
+    ///UTC build time (RFC 3339)
+    pub build_time: Option<&'static str>,
+    pub rustc_version: Option<&'static str>,
+    ///Cargo profile (`debug` or `release`)
+    pub profile: Option<&'static str>,
+    pub target: Option<&'static str>,
+}
+
+///Build provenance of the crate where the macro is expanded. See `bt_core_config::build::emit_build_info()`.
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::app_info::BuildInfo {
+            git_commit: option_env!("BT_BUILD_GIT_COMMIT"),
+            git_dirty: match option_env!("BT_BUILD_GIT_DIRTY") {
+                Some(dirty) => Some(dirty == "true"),
+                None => None,
+            },
+            build_time: option_env!("BT_BUILD_TIME"),
+            rustc_version: option_env!("BT_BUILD_RUSTC_VERSION"),
+            profile: option_env!("BT_BUILD_PROFILE"),
+            target: option_env!("BT_BUILD_TARGET"),
+        }
+    };
+}
+
+///Cargo metadata of the crate where the macro is expanded (`CARGO_PKG_*` and `CARGO_BIN_NAME`).
+#[macro_export]
+macro_rules! cargo_pkg {
+    () => {
+        $crate::app_info::CargoPkg {
+            pkg_name: option_env!("CARGO_PKG_NAME"),
+            pkg_version: option_env!("CARGO_PKG_VERSION"),
+            pkg_authors: option_env!("CARGO_PKG_AUTHORS"),
+            pkg_desc: option_env!("CARGO_PKG_DESCRIPTION"),
+            pkg_repository: option_env!("CARGO_PKG_REPOSITORY"),
+            pkg_homepage: option_env!("CARGO_PKG_HOMEPAGE"),
+            pkg_license: option_env!("CARGO_PKG_LICENSE"),
+            bin_name: option_env!("CARGO_BIN_NAME"),
+        }
+    };
+}
+
+///Builds an `AppInfo` from the Cargo metadata and build provenance of the calling crate.
+/// Fields of `CargoPkg` can be overridden: `app_info!(pkg_name: "my-service", pkg_version: "2.0.0")`.
+#[macro_export]
+macro_rules! app_info {
+    ($($field:ident : $value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut cargo_pkg = $crate::cargo_pkg!();
+        $( cargo_pkg.$field = Some($value); )*
+        let mut app_info = $crate::app_info::AppInfo::new(cargo_pkg, $crate::app_info::UNKNOWN_APP_NAME, $crate::app_info::UNKNOWN_APP_VERSION, "", "");
+        app_info.build = $crate::build_info!();
+        app_info
+    }};
 }
 
 impl AppInfo {
@@ -26,7 +124,7 @@ impl AppInfo {
     #[cfg(debug_assertions)]
     #[deprecated(
         since = "0.2.1",
-        note = "This function is for testing only. Avoid using in production.")]
+        note = "This function is for testing only. Avoid using in production. Use app_info!() instead.")]
     pub fn get_app_info(default_name: &'static str, default_version: &'static str, default_authors: &'static str, default_desciption: &'static str) -> Self{
         println!("AppInfo::get_app_info() is safe to run in tests only!");
 
@@ -65,11 +163,15 @@ impl AppInfo {
 
 
         let (pkg_full_path, pkg_root) = Self::get_exe_dir_folder(pkg_name.to_string());
-        Self { package_name: pkg_name, version: pkg_version, authors: pkg_authors, description: pkg_desc, pkg_full_folder_path: pkg_full_path, pkg_root_folder_only: pkg_root }
+        Self { package_name: pkg_name, version: pkg_version, authors: pkg_authors, description: pkg_desc,
+               repository: option_env!("CARGO_PKG_REPOSITORY").unwrap_or(""), homepage: option_env!("CARGO_PKG_HOMEPAGE").unwrap_or(""),
+               license: option_env!("CARGO_PKG_LICENSE").unwrap_or(""), bin_name: None, display_name: None, build: BuildInfo::default(),
+               pkg_full_folder_path: pkg_full_path, pkg_root_folder_only: pkg_root }
     }
 
 
     pub fn new(cargo_pkg: CargoPkg, default_name: &'static str, default_version: &'static str, default_authors: &'static str, default_desciption: &'static str) -> Self{
+        runtime_info::mark_start();
         let pkg_name: &'static str = match cargo_pkg.pkg_name{
                                             Some(v) => if v.trim().is_empty() {
                                                                     default_name
@@ -105,8 +207,84 @@ impl AppInfo {
 
 
 
+        let non_empty = |value: Option<&'static str>| value.map(|v| v.trim()).filter(|v| !v.is_empty());
+
         let (pkg_full_path, pkg_root) = Self::get_exe_dir_folder(pkg_name.to_string());
-        Self { package_name: pkg_name, version: pkg_version, authors: pkg_authors, description: pkg_desc, pkg_full_folder_path: pkg_full_path, pkg_root_folder_only: pkg_root }
+        Self { package_name: pkg_name, version: pkg_version, authors: pkg_authors, description: pkg_desc,
+               repository: non_empty(cargo_pkg.pkg_repository).unwrap_or(""), homepage: non_empty(cargo_pkg.pkg_homepage).unwrap_or(""),
+               license: non_empty(cargo_pkg.pkg_license).unwrap_or(""), bin_name: non_empty(cargo_pkg.bin_name), display_name: None, build: BuildInfo::default(),
+               pkg_full_folder_path: pkg_full_path, pkg_root_folder_only: pkg_root }
+    }
+
+    ///Sets the name shown to users, keeping `package_name` and the binary name unchanged
+    pub fn with_display_name(mut self, display_name: &'static str) -> Self {
+        self.display_name = Some(display_name.trim()).filter(|n| !n.is_empty());
+        self
+    }
+
+    ///Name shown to users: the display name, or `package_name` when none is set
+    pub fn display_name(&self) -> &str {
+        self.display_name.unwrap_or(self.package_name)
+    }
+
+    ///Name of the executable: `bin_name` when known, otherwise `get_app_name(None)`
+    pub fn binary_name(&self) -> String {
+        match self.bin_name {
+            Some(bin_name) => bin_name.to_owned(),
+            None => Self::get_app_name(None),
+        }
+    }
+
+    ///Parsed `version`
+    pub fn parse_version(&self) -> Result<Version, String> {
+        self.version.parse()
+    }
+
+    ///Document for version/health endpoints. The keys are always present:
+    /// `name`, `display_name`, `version`, `authors` (list), `description`, `exe_folder` and `environment` (null when None or unknown).
+    /// `environment` is usually `AppConfig::get_environment()`.
+    pub fn to_json(&self, environment: Option<&str>) -> Value {
+        let authors: Vec<&str> = self.authors.split(':').map(|a| a.trim()).filter(|a| !a.is_empty()).collect();
+        json!({
+            "name": self.package_name,
+            "display_name": self.display_name(),
+            "version": self.version,
+            "authors": authors,
+            "description": self.description,
+            "exe_folder": self.pkg_full_folder_path,
+            "environment": environment,
+        })
+    }
+
+    ///Host and process facts (hostname, PID, user, OS, CPUs, memory, container, uptime).
+    /// Uptime counts from the first `AppInfo` built.
+    pub fn runtime_info(&self) -> RuntimeInfo {
+        RuntimeInfo::collect()
+    }
+
+    ///Config, data, cache, log and runtime directories of the application, with the source of each path.
+    pub fn app_dirs(&self) -> AppDirs {
+        AppDirs::resolve(self)
+    }
+
+    ///One line for startup logs, e.g. `my-service 1.2.0 (commit 3f2a9c1-dirty, built 2026-01-05T10:00:00Z, release, x86_64-unknown-linux-gnu, rustc 1.90.0)`.
+    /// Unknown build values are left out.
+    pub fn build_banner(&self) -> String {
+        let build = &self.build;
+        let commit = build.git_commit.map(|c| {
+            let short = c.get(..7).unwrap_or(c);
+            format!("commit {}{}", short, if build.git_dirty == Some(true) { "-dirty" } else { "" })
+        });
+        let details: Vec<String> = [commit, build.build_time.map(|t| format!("built {}", t)), build.profile.map(|p| p.to_owned()),
+                                    build.target.map(|t| t.to_owned()), build.rustc_version.map(|r| r.to_owned())]
+            .into_iter()
+            .flatten()
+            .collect();
+        if details.is_empty() {
+            format!("{} {}", self.package_name, self.version)
+        } else {
+            format!("{} {} ({})", self.package_name, self.version, details.join(", "))
+        }
     }
 
     fn get_current_exe_path() -> Option<PathBuf>{
@@ -128,6 +306,7 @@ impl AppInfo {
         None
     }
 
+    ///Prefer `app_dirs()`, which records where each directory comes from.
     pub fn get_exe_dir_folder(package_name: String) -> (Option<String>, String){
         let pkg_name = if !package_name.trim().is_empty() { package_name.trim() } else { "." };
 
@@ -160,26 +339,26 @@ impl AppInfo {
         (pkg_full_path, pkg_root)
     }
 
+    ///`package_name` when not empty. Otherwise the executable file stem without the `-<16 hex digits>` hash Cargo adds
+    /// to test binaries. Use `binary_name` to prefer the binary target captured at compile time.
     pub fn get_app_name(package_name: Option<&str>) -> String{
-       let pkg_name = package_name.unwrap_or("").trim();
-       if !pkg_name.is_empty() 
-        { pkg_name.to_string() 
-        } else { //Get the name from current executable
-            //gets the full path to the currently running executable.
-            //extracts the filename without its extension (path.file_stem())
-            //remove the suffix
-            let full_name = env::current_exe()
-                .ok()
-                .and_then(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
-                .unwrap_or_else(|| "BT_UnkownApp".to_string())
-                .split('-').next().map(|base| base.to_string()).unwrap_or("BT_UnkownApp".to_owned());
-
-            if !full_name.trim().is_empty(){
-                full_name.trim().to_owned()
-            }else{
-                "BT_UnkownApp".to_string()
-            }
+        let pkg_name = package_name.unwrap_or("").trim();
+        if !pkg_name.is_empty() {
+            return pkg_name.to_string();
         }
+        env::current_exe()
+            .ok()
+            .and_then(|path| path.file_stem().map(|s| strip_test_hash(&s.to_string_lossy()).trim().to_owned()))
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| UNKNOWN_APP_NAME.to_string())
+    }
+}
+
+///Removes the `-<16 hex digits>` suffix of Cargo test binaries (e.g. `my-service-0123456789abcdef` is `my-service`)
+fn strip_test_hash(file_stem: &str) -> &str {
+    match file_stem.rsplit_once('-') {
+        Some((name, hash)) if !name.is_empty() && hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()) => name,
+        _ => file_stem,
     }
 }
 
@@ -189,7 +368,7 @@ impl AppInfo {
 //***********/
 #[cfg(test)]
 mod app_config_tests {
-    use crate::app_info::AppInfo;
+    use crate::app_info::{strip_test_hash, AppInfo, BuildInfo, CargoPkg};
 
     #[test]
     pub fn test_get_app_name_with_package_success(){
@@ -203,6 +382,65 @@ mod app_config_tests {
         assert_eq!(name,"bt_core_config");
     }
 
+    #[test]
+    pub fn test_strip_test_hash(){
+        assert_eq!(strip_test_hash("my-service-0123456789abcdef"),"my-service");
+        assert_eq!(strip_test_hash("my-service"),"my-service");
+        assert_eq!(strip_test_hash("my-service-v2"),"my-service-v2");
+        assert_eq!(strip_test_hash("tool-0123456789abcdeg"),"tool-0123456789abcdeg");
+        assert_eq!(strip_test_hash("-0123456789abcdef"),"-0123456789abcdef");
+    }
+
+    #[test]
+    pub fn test_display_and_binary_name(){
+        let app_info = crate::app_info!(pkg_name: "bt-ai", bin_name: "bt-ai-server").with_display_name("BACHUETECH AI");
+        assert_eq!(app_info.display_name(),"BACHUETECH AI");
+        assert_eq!(app_info.package_name,"bt-ai");
+        assert_eq!(app_info.binary_name(),"bt-ai-server");
+        let app_info = crate::app_info!(pkg_name: "bt-ai").with_display_name(" ");
+        assert_eq!(app_info.display_name(),"bt-ai");
+        assert_eq!(app_info.binary_name(),"bt_core_config");
+    }
+
+    #[test]
+    pub fn test_app_info_macro(){
+        let app_info = crate::app_info!();
+        assert_eq!(app_info.package_name,"bt_core_config");
+        assert_eq!(app_info.version,env!("CARGO_PKG_VERSION"));
+        assert_eq!(app_info.description,"Core Configuration. Application and Server.");
+        assert_eq!(app_info.bin_name,None);
+        let app_info = crate::app_info!(pkg_name: "my-service", pkg_license: "MIT", bin_name: "my-service");
+        assert_eq!(app_info.package_name,"my-service");
+        assert_eq!(app_info.license,"MIT");
+        assert_eq!(app_info.bin_name,Some("my-service"));
+        assert_eq!(app_info.authors,"calvarez <calvarez@bachuetech.biz>");
+    }
+
+    #[test]
+    pub fn test_build_banner(){
+        let mut app_info = crate::app_info!(pkg_name: "my-service", pkg_version: "1.2.0");
+        assert_eq!(app_info.build_banner(),"my-service 1.2.0");
+        app_info.build = BuildInfo{ git_commit: Some("3f2a9c1d0e5b"), git_dirty: Some(true), build_time: Some("2026-01-05T10:00:00Z"),
+                                    rustc_version: Some("rustc 1.90.0"), profile: Some("release"), target: Some("x86_64-unknown-linux-gnu") };
+        assert_eq!(app_info.build_banner(),
+                   "my-service 1.2.0 (commit 3f2a9c1-dirty, built 2026-01-05T10:00:00Z, release, x86_64-unknown-linux-gnu, rustc 1.90.0)");
+    }
+
+    #[test]
+    pub fn test_to_json(){
+        let app_info = AppInfo::new(CargoPkg{ pkg_name: Some("my-service"), pkg_version: Some("1.2.0"), pkg_authors: Some("Ann <a@x.io>:Bob"), ..Default::default() },
+                                    "default", "0.0.0", "", "Service");
+        let doc = app_info.to_json(Some("prod"));
+        assert_eq!(doc["name"], "my-service");
+        assert_eq!(doc["display_name"], "my-service");
+        assert_eq!(doc["authors"], serde_json::json!(["Ann <a@x.io>", "Bob"]));
+        assert_eq!(doc["description"], "Service");
+        assert_eq!(doc["environment"], "prod");
+        assert!(doc["exe_folder"].is_string());
+        assert!(app_info.to_json(None)["environment"].is_null());
+        assert_eq!(serde_json::to_value(&app_info).unwrap(), app_info.to_json(None));
+    }
+
     #[test]
     pub fn test_get_app_name_with_empty_success(){
         let name = AppInfo::get_app_name(Some(""));
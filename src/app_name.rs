@@ -0,0 +1,118 @@
+use std::fmt;
+
+use yaml_rust2::Yaml;
+
+use crate::app_info::{AppInfo, UNKNOWN_APP_NAME};
+use crate::schema::environment_block;
+
+///Rule that chose the application name, in order of precedence
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AppNameSource {
+    ///`app_name:` of the running environment block in app-config
+    EnvironmentConfig,
+    ///Top level `app_name:` of app-config
+    ConfigFile,
+    ///`AppInfo::with_display_name`
+    DisplayName,
+    ///`AppInfo.package_name` (Cargo package name)
+    PackageName,
+    ///Name of the running binary (`AppInfo::binary_name`)
+    BinaryName,
+    ///`UNKNOWN_APP_NAME`
+    Unknown,
+}
+
+impl fmt::Display for AppNameSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppNameSource::EnvironmentConfig => write!(f, "'app_name' key of the environment"),
+            AppNameSource::ConfigFile => write!(f, "'app_name' key of the configuration file"),
+            AppNameSource::DisplayName => write!(f, "application display name"),
+            AppNameSource::PackageName => write!(f, "Cargo package name"),
+            AppNameSource::BinaryName => write!(f, "binary name"),
+            AppNameSource::Unknown => write!(f, "default"),
+        }
+    }
+}
+
+///Application name and the rule that chose it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedAppName {
+    pub name: String,
+    pub source: AppNameSource,
+}
+
+///Application name used by `AppConfig::get_app_name`. The first non empty value wins:
+/// 1. `app_name:` of the `environment` block (e.g. `BACHUETECH AI (staging)`)
+/// 2. top level `app_name:` of the file
+/// 3. `app_info.display_name`
+/// 4. `app_info.package_name`, unless it is `UNKNOWN_APP_NAME`
+/// 5. `app_info.binary_name()` (binary target or executable name)
+/// 6. `UNKNOWN_APP_NAME`
+pub fn resolve_app_name(app_config: &Yaml, environment: &str, app_info: &AppInfo) -> ResolvedAppName {
+    let configured = |value: &Yaml| value.as_str().map(|v| v.trim().to_owned()).filter(|v| !v.is_empty());
+    let binary_name = app_info.binary_name();
+    let candidates = [
+        (configured(&environment_block(app_config, environment)["app_name"]), AppNameSource::EnvironmentConfig),
+        (configured(&app_config["app_name"]), AppNameSource::ConfigFile),
+        (app_info.display_name.map(|n| n.to_owned()), AppNameSource::DisplayName),
+        (Some(app_info.package_name.to_owned()).filter(|n| n != UNKNOWN_APP_NAME), AppNameSource::PackageName),
+        (Some(binary_name).filter(|n| n != UNKNOWN_APP_NAME), AppNameSource::BinaryName),
+    ];
+    candidates.into_iter()
+        .find_map(|(name, source)| name.filter(|n| !n.trim().is_empty()).map(|name| ResolvedAppName{ name, source }))
+        .unwrap_or(ResolvedAppName{ name: UNKNOWN_APP_NAME.to_owned(), source: AppNameSource::Unknown })
+}
+
+///Folder name of the application: `$XDG_CONFIG_HOME/<name>` and `/etc/<name>` searched for configuration files,
+/// and the `AppDirs` directories. It is `app_info.binary_name()`, or the executable name without an `AppInfo`
+/// (e.g. `ServerConfig`), so every loader uses the same folder. Configured and display names may contain spaces,
+/// and the package name is not known to loaders without an `AppInfo`.
+pub fn app_dir_name(app_info: Option<&AppInfo>) -> String {
+    match app_info {
+        Some(app_info) => app_info.binary_name(),
+        None => AppInfo::get_app_name(None),
+    }
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod app_name_tests {
+    use yaml_rust2::YamlLoader;
+
+    use crate::app_info::{AppInfo, CargoPkg, UNKNOWN_APP_NAME, UNKNOWN_APP_VERSION};
+
+    use super::{app_dir_name, resolve_app_name, AppNameSource};
+
+    #[test]
+    pub fn test_resolve_app_name_order(){
+        let yaml = YamlLoader::load_from_str("app_name: BACHUETECH AI\nenvironments:\n  staging:\n    app_name: BACHUETECH AI (staging)\n  prod:\n    app_name: ' '\n").unwrap().remove(0);
+        let app_info = AppInfo::new(CargoPkg{ pkg_name: Some("bt-ai"), ..Default::default() }, UNKNOWN_APP_NAME, UNKNOWN_APP_VERSION, "", "");
+        let resolved = resolve_app_name(&yaml, "staging", &app_info);
+        assert_eq!(resolved.name, "BACHUETECH AI (staging)");
+        assert_eq!(resolved.source, AppNameSource::EnvironmentConfig);
+        assert_eq!(resolve_app_name(&yaml, "prod", &app_info).source, AppNameSource::ConfigFile);
+
+        let no_name = YamlLoader::load_from_str("environments:\n  prod:\n").unwrap().remove(0);
+        assert_eq!(resolve_app_name(&no_name, "prod", &app_info).source, AppNameSource::PackageName);
+        let resolved = resolve_app_name(&no_name, "prod", &app_info.clone().with_display_name("BT AI"));
+        assert_eq!((resolved.name.as_str(), resolved.source), ("BT AI", AppNameSource::DisplayName));
+
+        let unknown = AppInfo::new(CargoPkg::default(), UNKNOWN_APP_NAME, UNKNOWN_APP_VERSION, "", "");
+        let resolved = resolve_app_name(&no_name, "prod", &unknown);
+        assert_eq!((resolved.name.as_str(), resolved.source), ("bt_core_config", AppNameSource::BinaryName));
+    }
+
+    #[test]
+    pub fn test_app_dir_name(){
+        //Package and binary names differ: folders use the binary name
+        let server = crate::app_info!(pkg_name: "bt-ai", bin_name: "bt-ai-server");
+        assert_eq!(app_dir_name(Some(&server)), "bt-ai-server");
+        let library = AppInfo::new(CargoPkg{ pkg_name: Some("bt-ai"), ..Default::default() }, UNKNOWN_APP_NAME, UNKNOWN_APP_VERSION, "", "");
+        assert_eq!(app_dir_name(Some(&library)), app_dir_name(None));
+        assert_eq!(app_dir_name(None), "bt_core_config");
+    }
+}
@@ -0,0 +1,270 @@
+//! Configuration lint/check tool.
+//!
+//! Usage: bt-config [--dir <config dir>] [--strict] [--verbose] [--force] [--app-version <version>] <command>
+//!   --app-version       Version of the application using the files, checked against min_app_version/max_app_version.
+//!                       The check is skipped without it.
+//!   check               Load every environment of app-config.yml and server-config.yml and report errors and warnings.
+//!   show <env>          Print the effective configuration of an environment. Secrets are redacted.
+//!   envs                List the environments of each file.
+//!   diff <envA> <envB>  Print the effective values that change between two environments.
+//!   init <env>...       Write commented starter files for the environments. Existing files are kept unless --force.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use bt_any_error::any_err::AnyErr;
+use bt_logger::{build_logger, LogLevel, LogTarget};
+use bt_core_config::app_config::AppConfig;
+use bt_core_config::app_info::AppInfo;
+use bt_core_config::diff::ConfigDiff;
+use bt_core_config::scaffold::{write_starter_configs, APP_CONFIG_FILE_NAME, SRV_CONFIG_FILE_NAME};
+use bt_core_config::schema::{ConfigSchema, Severity, ValidationMode, APP_CONFIG_SCHEMA, SRV_CONFIG_SCHEMA};
+use bt_core_config::server_config::ServerConfig;
+use bt_yaml_utils::get_yaml_from_string;
+use yaml_rust2::Yaml;
+
+const DEFAULT_CONFIG_DIR: &str = "config/core";
+const APP_CONFIG_FILE: &str = APP_CONFIG_FILE_NAME;
+const SRV_CONFIG_FILE: &str = SRV_CONFIG_FILE_NAME;
+const REDACTED: &str = "********";
+const SECRET_MARKERS: [&str; 8] = ["password", "passwd", "secret", "token", "key", "apikey", "credential", "credentials"];
+
+const USAGE: &str = "Usage: bt-config [--dir <config dir>] [--strict] [--verbose] [--force] [--app-version <version>] <command>
+Commands:
+  check               Load every environment and report errors and warnings
+  show <env>          Print the effective configuration of an environment (secrets redacted)
+  envs                List the environments of each file
+  diff <envA> <envB>  Print the effective values that change between two environments
+  init <env>...       Write starter configuration files for the environments (--force to overwrite)";
+
+struct ConfigFile {
+    path: PathBuf,
+    content: String,
+    yaml: Yaml,
+}
+
+struct Cli {
+    config_dir: PathBuf,
+    validation: ValidationMode,
+    log_level: LogLevel,
+    force: bool,
+    ///Version of the application using the files. None skips the min_app_version/max_app_version check.
+    app_version: Option<String>,
+    command: Vec<String>,
+}
+
+fn main() -> ExitCode {
+    let cli = match parse_args(std::env::args().skip(1).collect()) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        },
+    };
+    //Findings are printed by the tool. The library log is only shown with --verbose.
+    build_logger("BACHUETECH", "BT_CONFIG", cli.log_level.clone(), LogTarget::STD_ERROR, None);
+
+    let result = match cli.command.iter().map(|c| c.as_str()).collect::<Vec<&str>>().as_slice() {
+        ["check"] => check(&cli),
+        ["show", env] => show(&cli, env),
+        ["envs"] => envs(&cli),
+        ["diff", env_a, env_b] => diff(&cli, env_a, env_b),
+        ["init", envs @ ..] if !envs.is_empty() => init(&cli, envs),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        },
+    };
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Cli, String> {
+    let mut cli = Cli{ config_dir: PathBuf::from(DEFAULT_CONFIG_DIR), validation: ValidationMode::Lenient, log_level: LogLevel::NONE, force: false, app_version: None, command: Vec::new() };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dir" | "-d" => cli.config_dir = PathBuf::from(args.next().ok_or("Missing value for --dir")?),
+            "--strict" => cli.validation = ValidationMode::Strict,
+            "--verbose" | "-v" => cli.log_level = LogLevel::VERBOSE,
+            "--force" | "-f" => cli.force = true,
+            "--app-version" => cli.app_version = Some(args.next().ok_or("Missing value for --app-version")?),
+            "--help" | "-h" => return Err("bt-config: check core configuration files".to_owned()),
+            other if other.starts_with('-') => return Err(format!("Unknown option '{}'", other)),
+            _ => cli.command.push(arg),
+        }
+    }
+    Ok(cli)
+}
+
+fn app_info() -> AppInfo {
+    bt_core_config::app_info!(pkg_name: "bt-config")
+}
+
+fn read_config(dir: &Path, file_name: &str) -> Result<ConfigFile, AnyErr> {
+    let path = dir.join(file_name);
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+    let yaml = get_yaml_from_string(&content).map_err(|e| format!("Invalid YAML in '{}': {}", path.display(), e))?;
+    Ok(ConfigFile{ path, content, yaml })
+}
+
+fn load_app(cli: &Cli, file: &ConfigFile, env: &str) -> Result<AppConfig, AnyErr> {
+    if !APP_CONFIG_SCHEMA.environments(&file.yaml).iter().any(|e| e == env) {
+        return Err(format!("Environment '{}' not found in '{}'", env, file.path.display()).into());
+    }
+    AppConfig::from_file(env, &app_info(), cli.app_version.as_deref(), &file.path, cli.validation)
+}
+
+fn load_srv(cli: &Cli, file: &ConfigFile, env: &str) -> Result<ServerConfig, AnyErr> {
+    if !SRV_CONFIG_SCHEMA.environments(&file.yaml).iter().any(|e| e == env) {
+        return Err(format!("Environment '{}' not found in '{}'", env, file.path.display()).into());
+    }
+    ServerConfig::new_with_validation(env, Some(&file.content), cli.validation)
+}
+
+///Effective values of both files for one environment. Server keys are already prefixed by `server.`
+fn effective_values(cli: &Cli, env: &str) -> Result<BTreeMap<String, String>, AnyErr> {
+    let mut values = load_app(cli, &read_config(&cli.config_dir, APP_CONFIG_FILE)?, env)?.effective_values();
+    values.extend(load_srv(cli, &read_config(&cli.config_dir, SRV_CONFIG_FILE)?, env)?.effective_values());
+    Ok(values)
+}
+
+///Masks the value when a word of the last key segment (split on `_` and `-`) is a secret marker, e.g. `api_key` but not `max_tokens`.
+fn redact(key: &str, value: &str) -> String {
+    let last = key.rsplit('.').next().unwrap_or(key).to_lowercase();
+    if last.split(['_', '-']).any(|word| SECRET_MARKERS.contains(&word)) { REDACTED.to_owned() } else { value.to_owned() }
+}
+
+///Validates and loads every environment of a file. `load` builds the configuration object for one environment
+/// and returns its warnings.
+fn check_file(cli: &Cli, schema: &ConfigSchema, file: &ConfigFile, other_envs: &[String], load: &dyn Fn(&str) -> Result<Vec<String>, AnyErr>) -> bool {
+    let mut ok = true;
+    let environments = schema.environments(&file.yaml);
+    println!("{} ({} environment(s))", file.path.display(), environments.len());
+    for env in &environments {
+        let violations = schema.validate(&file.yaml, env);
+        let errors = violations.iter().filter(|v| v.severity == Severity::Error || cli.validation == ValidationMode::Strict).count();
+        let (load_warnings, load_error) = match errors {
+            0 => match load(env) {
+                Ok(warnings) => (warnings, None),
+                Err(e) => (Vec::new(), Some(e)),
+            },
+            _ => (Vec::new(), None),
+        };
+        let failed = errors > 0 || load_error.is_some();
+        println!("  {}: {}", env, if failed { "FAILED" } else { "ok" });
+        for v in &violations {
+            let level = if v.severity == Severity::Error || cli.validation == ValidationMode::Strict { "error" } else { "warning" };
+            println!("    {}: {}", level, v);
+        }
+        if let Some(e) = load_error {
+            println!("    error: {}", e);
+        }
+        for warning in &load_warnings {
+            println!("    warning: {}", warning);
+        }
+        if !other_envs.contains(env) {
+            println!("    warning: environment '{}' is not defined in the other configuration file", env);
+        }
+        ok &= !failed;
+    }
+    ok
+}
+
+fn check(cli: &Cli) -> Result<bool, AnyErr> {
+    let app = read_config(&cli.config_dir, APP_CONFIG_FILE)?;
+    let srv = read_config(&cli.config_dir, SRV_CONFIG_FILE)?;
+    let app_envs = APP_CONFIG_SCHEMA.environments(&app.yaml);
+    let srv_envs = SRV_CONFIG_SCHEMA.environments(&srv.yaml);
+
+    //Static files are only checked at load time when required; the tool reports them for every environment
+    let app_ok = check_file(cli, &APP_CONFIG_SCHEMA, &app, &srv_envs, &|env| {
+        load_app(cli, &app, env).map(|ac| ac.check_files_app_path().err().map(|e| e.to_string()).into_iter().collect())
+    });
+    let srv_ok = check_file(cli, &SRV_CONFIG_SCHEMA, &srv, &app_envs, &|env| load_srv(cli, &srv, env).map(|_| Vec::new()));
+    Ok(app_ok && srv_ok)
+}
+
+fn show(cli: &Cli, env: &str) -> Result<bool, AnyErr> {
+    for (key, value) in effective_values(cli, env)? {
+        println!("{}: {}", key, redact(&key, &value));
+    }
+    Ok(true)
+}
+
+fn envs(cli: &Cli) -> Result<bool, AnyErr> {
+    for (schema, file_name) in [(&APP_CONFIG_SCHEMA, APP_CONFIG_FILE), (&SRV_CONFIG_SCHEMA, SRV_CONFIG_FILE)] {
+        let file = read_config(&cli.config_dir, file_name)?;
+        println!("{}", file.path.display());
+        for env in schema.list_environments(&file.yaml) {
+            println!("  {}{}", env.name, if env.is_empty { " (empty)" } else { "" });
+        }
+    }
+    Ok(true)
+}
+
+fn diff(cli: &Cli, env_a: &str, env_b: &str) -> Result<bool, AnyErr> {
+    let diff = ConfigDiff::between(&effective_values(cli, env_a)?, &effective_values(cli, env_b)?);
+    for (key, a) in &diff.removed {
+        println!("- {}: {}", key, redact(key, a));
+    }
+    for (key, (a, b)) in &diff.changed {
+        println!("~ {}: {} -> {}", key, redact(key, a), redact(key, b));
+    }
+    for (key, b) in &diff.added {
+        println!("+ {}: {}", key, redact(key, b));
+    }
+    Ok(true)
+}
+
+fn init(cli: &Cli, environments: &[&str]) -> Result<bool, AnyErr> {
+    for path in write_starter_configs(&cli.config_dir, environments, cli.force)? {
+        println!("created {}", path.display());
+    }
+    Ok(true)
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod bt_config_tests {
+    use bt_core_config::schema::ValidationMode;
+
+    use super::{parse_args, redact, REDACTED};
+
+    #[test]
+    pub fn test_parse_args_success(){
+        let cli = parse_args(vec!["--dir".to_owned(), "cfg".to_owned(), "--strict".to_owned(), "show".to_owned(), "dev".to_owned()]).unwrap();
+        assert_eq!(cli.config_dir.to_str(), Some("cfg"));
+        assert_eq!(cli.validation, ValidationMode::Strict);
+        assert_eq!(cli.command, vec!["show", "dev"]);
+        assert!(!cli.force);
+        assert!(parse_args(vec!["--force".to_owned(), "init".to_owned(), "dev".to_owned()]).unwrap().force);
+        assert!(parse_args(vec!["--bogus".to_owned()]).is_err());
+        let cli = parse_args(vec!["--app-version".to_owned(), "1.4.0".to_owned(), "check".to_owned()]).unwrap();
+        assert_eq!(cli.app_version.as_deref(), Some("1.4.0"));
+        assert_eq!(parse_args(vec!["check".to_owned()]).unwrap().app_version, None);
+    }
+
+    #[test]
+    pub fn test_redact(){
+        assert_eq!(redact("database.password", "abc"), REDACTED);
+        assert_eq!(redact("agent.api_key", "abc"), REDACTED);
+        assert_eq!(redact("agent.host", "localhost"), "localhost");
+        assert_eq!(redact("auth.access-token", "abc"), REDACTED);
+        assert_eq!(redact("auth.apiKey", "abc"), REDACTED);
+        //Markers are whole words of the key
+        assert_eq!(redact("agent.max_tokens", "4096"), "4096");
+        assert_eq!(redact("monkey", "abc"), "abc");
+        assert_eq!(redact("ui.keyboard_layout", "us"), "us");
+    }
+}
@@ -0,0 +1,136 @@
+//! Build script helpers. Add `bt_core_config` to `[build-dependencies]` and call them from `build.rs`.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bt_any_error::any_err::AnyErr;
+
+use crate::embedded::{validate_config_pair, CHECKED_DIRS_ENV_VAR};
+use crate::scaffold::{APP_CONFIG_FILE_NAME, SRV_CONFIG_FILE_NAME};
+use crate::schema::ValidationMode;
+use crate::utils::format_utc;
+
+///Folders validated by `check_core_config` in this build script
+static CHECKED_DIRS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+///Validates the folder embedded with `embed_core_config!` and fails the build with the list of problems.
+/// `dir` is relative to the crate root and must be written as in `embed_core_config!`, which does not compile without this check.
+/// `environments` must be defined in both files. Cargo re-runs the check when a file changes.
+///
+/// `bt_core_config::build::check_core_config("config/core", &["dev", "prod"]);`
+pub fn check_core_config(dir: &str, environments: &[&str]) {
+    if let Err(e) = validate_config_dir(dir, environments, ValidationMode::Strict) {
+        eprintln!("error: invalid core configuration in '{}':\n{}", dir, e);
+        std::process::exit(1);
+    }
+    let mut checked = CHECKED_DIRS.lock().unwrap_or_else(|e| e.into_inner());
+    checked.push(dir.to_owned());
+    println!("cargo:rustc-env={}={}", CHECKED_DIRS_ENV_VAR, checked.join(":"));
+}
+
+///Captures the build provenance read by `app_info!()` / `build_info!()`: git commit and dirty flag, UTC build time
+/// (`SOURCE_DATE_EPOCH` when set, for reproducible builds), rustc version, profile and target triple.
+/// Values that cannot be determined (e.g. no git repository) are left out.
+///
+/// `bt_core_config::build::emit_build_info();`
+pub fn emit_build_info() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+    //Any rerun-if line disables Cargo's default of re-running on every package change, so the sources are watched too.
+    //They keep the dirty flag current; git files keep the commit current, also from a workspace member.
+    for path in [manifest_dir.join("src"), manifest_dir.join("Cargo.toml")].into_iter().chain(git_watch_paths(&manifest_dir)) {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    if let Some(commit) = command_output(&manifest_dir, "git", &["rev-parse", "HEAD"]) {
+        println!("cargo:rustc-env=BT_BUILD_GIT_COMMIT={}", commit);
+        let dirty = command_output(&manifest_dir, "git", &["status", "--porcelain"]).is_some_and(|changes| !changes.is_empty());
+        println!("cargo:rustc-env=BT_BUILD_GIT_DIRTY={}", dirty);
+    }
+    let build_secs = env::var("SOURCE_DATE_EPOCH").ok().and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default());
+    println!("cargo:rustc-env=BT_BUILD_TIME={}", format_utc(build_secs));
+    if let Some(rustc_version) = command_output(&manifest_dir, &env::var("RUSTC").unwrap_or("rustc".to_owned()), &["--version"]) {
+        println!("cargo:rustc-env=BT_BUILD_RUSTC_VERSION={}", rustc_version);
+    }
+    for (var, name) in [("PROFILE", "BT_BUILD_PROFILE"), ("TARGET", "BT_BUILD_TARGET")] {
+        if let Ok(value) = env::var(var) {
+            println!("cargo:rustc-env={}={}", name, value);
+        }
+    }
+}
+
+///Git files that change with the commit or the index: `HEAD`, `index`, the current branch ref and `packed-refs`.
+/// The repository is found from `dir` with git, so it may be any parent folder (workspace root) or a worktree.
+fn git_watch_paths(dir: &Path) -> Vec<PathBuf> {
+    let git_path = |arg: &str| command_output(dir, "git", &["rev-parse", arg]).map(|p| dir.join(p));
+    let (Some(git_dir), Some(common_dir)) = (git_path("--git-dir"), git_path("--git-common-dir")) else {
+        return Vec::new();
+    };
+    let mut paths = vec![git_dir.join("HEAD"), git_dir.join("index")];
+    if let Ok(head) = fs::read_to_string(git_dir.join("HEAD")) && let Some(reference) = head.trim().strip_prefix("ref: ") {
+        paths.push(common_dir.join(reference));
+    }
+    paths.push(common_dir.join("packed-refs"));
+    //A missing path would make Cargo re-run the script on every build
+    paths.retain(|p| p.exists());
+    paths
+}
+
+///Trimmed stdout of a successful command run in `dir`
+fn command_output(dir: &Path, program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).current_dir(dir).output().ok()?;
+    if output.status.success() { Some(String::from_utf8_lossy(&output.stdout).trim().to_owned()) } else { None }
+}
+
+///Reads `app-config.yml` and `server-config.yml` from `dir` (relative to `CARGO_MANIFEST_DIR`) and validates them.
+pub fn validate_config_dir(dir: &str, environments: &[&str], validation: ValidationMode) -> Result<(), AnyErr> {
+    let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join(dir);
+    let mut contents = Vec::new();
+    for file_name in [APP_CONFIG_FILE_NAME, SRV_CONFIG_FILE_NAME] {
+        let path = root.join(file_name);
+        println!("cargo:rerun-if-changed={}", path.display());
+        contents.push(fs::read_to_string(&path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?);
+    }
+    validate_config_pair(&contents[0], &contents[1], environments, validation)
+}
+
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod build_tests {
+    use std::process::Command;
+
+    use super::git_watch_paths;
+
+    #[test]
+    pub fn test_git_watch_paths_workspace_member(){
+        let root = std::env::temp_dir().join(format!("bt_core_config_build_{}", std::process::id()));
+        let member = root.join("crates").join("member");
+        std::fs::create_dir_all(&member).unwrap();
+        let git = |args: &[&str]| Command::new("git").args(["-c", "user.name=t", "-c", "user.email=t@t"]).args(args).current_dir(&root).output()
+            .is_ok_and(|o| o.status.success());
+        if !git(&["init", "-q", "-b", "main"]) {
+            //git is not installed
+            std::fs::remove_dir_all(&root).unwrap();
+            return;
+        }
+        std::fs::write(member.join("lib.rs"), "").unwrap();
+        assert!(git(&["add", "."]) && git(&["commit", "-q", "-m", "init"]));
+
+        let paths = git_watch_paths(&member);
+        let git_dir = root.join(".git").canonicalize().unwrap();
+        let watched: Vec<_> = paths.iter().map(|p| p.canonicalize().unwrap()).collect();
+        assert!(watched.contains(&git_dir.join("HEAD")));
+        assert!(watched.contains(&git_dir.join("index")));
+        assert!(watched.contains(&git_dir.join("refs/heads/main")));
+        assert!(git_watch_paths(&std::env::temp_dir()).iter().all(|p| p.exists()));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
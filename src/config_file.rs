@@ -0,0 +1,136 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::env;
+
+use bt_any_error::any_err::AnyErr;
+use bt_logger::log_info;
+use yaml_rust2::Yaml;
+
+use crate::app_name::app_dir_name;
+use crate::config_value::parse_yaml;
+
+///Where a configuration file was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchLocation {
+    ///Path set in an environment variable (e.g. `BT_APP_CONFIGYMLFILE`)
+    EnvVar(String),
+    ///Relative to the current working directory
+    WorkingDir,
+    ///Relative to the folder of the running executable
+    ExeDir,
+    ///`$XDG_CONFIG_HOME/<app>` (`$HOME/.config/<app>` when not set)
+    XdgConfigHome,
+    ///`/etc/<app>`
+    Etc,
+    ///Path given by the application (e.g. `AppConfig::from_file`)
+    Explicit,
+}
+
+impl fmt::Display for SearchLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchLocation::EnvVar(name) => write!(f, "env variable {}", name),
+            SearchLocation::WorkingDir => write!(f, "working directory"),
+            SearchLocation::ExeDir => write!(f, "executable directory"),
+            SearchLocation::XdgConfigHome => write!(f, "XDG config home"),
+            SearchLocation::Etc => write!(f, "/etc"),
+            SearchLocation::Explicit => write!(f, "explicit path"),
+        }
+    }
+}
+
+///Path of a configuration file and the search location it came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigLocation {
+    pub path: PathBuf,
+    pub location: SearchLocation,
+}
+
+///Candidate paths of a configuration file, in search order: `<working dir>/<relative_path>`, `<exe dir>/<relative_path>`,
+/// `$XDG_CONFIG_HOME/<app_name>/<file name>` and `/etc/<app_name>/<file name>` (Unix only).
+pub fn search_paths(relative_path: &str, app_name: &str) -> Vec<ConfigLocation> {
+    let file_name = Path::new(relative_path).file_name().map(PathBuf::from).unwrap_or_default();
+    let mut candidates = Vec::new();
+    if let Ok(cwd) = env::current_dir() {
+        candidates.push(ConfigLocation{ path: cwd.join(relative_path), location: SearchLocation::WorkingDir });
+    }
+    if let Some(exe_dir) = env::current_exe().ok().and_then(|p| p.parent().map(|d| d.to_path_buf())) {
+        candidates.push(ConfigLocation{ path: exe_dir.join(relative_path), location: SearchLocation::ExeDir });
+    }
+    let xdg_home = env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()).map(PathBuf::from)
+        .or_else(|| env::home_dir().map(|home| home.join(".config")));
+    if let Some(xdg_home) = xdg_home {
+        candidates.push(ConfigLocation{ path: xdg_home.join(app_name).join(&file_name), location: SearchLocation::XdgConfigHome });
+    }
+    if cfg!(unix) {
+        candidates.push(ConfigLocation{ path: Path::new("/etc").join(app_name).join(&file_name), location: SearchLocation::Etc });
+    }
+    candidates.dedup_by(|a, b| a.path == b.path);
+    candidates
+}
+
+///Finds a configuration file. A path set in `env_var_name` is used as is and must exist.
+/// Otherwise the first existing search path (see `search_paths`) for `app_dir_name`. Ok(None) if none exists.
+pub fn find_config_file(env_var_name: &str, relative_path: &str) -> Result<Option<ConfigLocation>, AnyErr> {
+    if let Ok(path) = env::var(env_var_name) {
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            return Err(format!("Configuration file '{}' set in {} not found.", path.display(), env_var_name).into());
+        }
+        return Ok(Some(ConfigLocation{ path, location: SearchLocation::EnvVar(env_var_name.to_owned()) }));
+    }
+    let found = search_paths(relative_path, &app_dir_name(None)).into_iter().find(|c| c.path.is_file());
+    if let Some(found) = &found {
+        log_info!("", "Using configuration file '{}' (from {}).", found.path.display(), found.location);
+    }
+    Ok(found)
+}
+
+///Same as `find_config_file`, but a missing file is an error listing every path searched.
+pub(crate) fn locate_config_file(env_var_name: &str, relative_path: &str) -> Result<ConfigLocation, AnyErr> {
+    match find_config_file(env_var_name, relative_path)? {
+        Some(found) => Ok(found),
+        None => {
+            let searched: Vec<String> = search_paths(relative_path, &app_dir_name(None)).iter()
+                .map(|c| format!("{} ({})", c.path.display(), c.location))
+                .collect();
+            Err(format!("Configuration file '{}' not found. Searched: {}", relative_path, searched.join(", ")).into())
+        },
+    }
+}
+
+///Reads and parses a configuration file
+pub(crate) fn read_config_file(path: &Path) -> Result<Yaml, AnyErr> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+    parse_yaml(&content).map_err(|e| format!("Invalid YAML in '{}': {}", path.display(), e).into())
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod config_file_tests {
+    use super::{find_config_file, locate_config_file, search_paths, SearchLocation};
+
+    #[test]
+    pub fn test_search_paths_order(){
+        let candidates = search_paths("config/core/app-config.yml", "my_app");
+        let locations: Vec<&SearchLocation> = candidates.iter().map(|c| &c.location).collect();
+        assert_eq!(locations.first(), Some(&&SearchLocation::WorkingDir));
+        assert!(locations.contains(&&SearchLocation::ExeDir));
+        let etc = candidates.iter().find(|c| c.location == SearchLocation::Etc).unwrap();
+        assert_eq!(etc.path.to_str(), Some("/etc/my_app/app-config.yml"));
+    }
+
+    #[test]
+    pub fn test_find_config_file(){
+        let found = find_config_file("BT_CORE_CONFIG_TEST_UNSET_FILE", "config/core/app-config.yml").unwrap().unwrap();
+        assert_eq!(found.location, SearchLocation::WorkingDir);
+        assert!(found.path.is_absolute());
+        assert!(find_config_file("BT_CORE_CONFIG_TEST_UNSET_FILE", "config/missing.yml").unwrap().is_none());
+        let err = locate_config_file("BT_CORE_CONFIG_TEST_UNSET_FILE", "config/missing.yml").unwrap_err();
+        assert!(err.to_string().contains("(executable directory)"));
+    }
+}
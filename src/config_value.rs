@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use bt_any_error::any_err::AnyErr;
+use bt_logger::log_info;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Number, Value};
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::{Yaml, YamlEmitter, YamlLoader};
+
+use crate::config_file::read_config_file;
+use crate::schema::ConfigSchema;
+
+///Converts a YAML node into a JSON value so it can be handed to serde.
+/// Hash keys are converted to strings. Aliases and bad values become null.
+pub(crate) fn yaml_to_json(yaml: &Yaml) -> Value {
+    match yaml {
+        Yaml::String(s) => Value::String(s.clone()),
+        Yaml::Integer(i) => Value::Number(Number::from(*i)),
+        Yaml::Real(r) => match r.parse::<f64>().ok().and_then(Number::from_f64) {
+                                Some(n) => Value::Number(n),
+                                None => Value::String(r.clone()),
+                            },
+        Yaml::Boolean(b) => Value::Bool(*b),
+        Yaml::Array(items) => Value::Array(items.iter().map(yaml_to_json).collect()),
+        Yaml::Hash(hash) => {
+            let mut map = Map::new();
+            for (key, val) in hash {
+                map.insert(yaml_key_to_string(key), yaml_to_json(val));
+            }
+            Value::Object(map)
+        },
+        Yaml::Null | Yaml::BadValue | Yaml::Alias(_) => Value::Null,
+    }
+}
+
+pub(crate) fn yaml_key_to_string(key: &Yaml) -> String {
+    match key {
+        Yaml::String(s) | Yaml::Real(s) => s.clone(),
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Boolean(b) => b.to_string(),
+        _ => format!("{:?}", key),
+    }
+}
+
+///Follows a dotted key path (e.g. `agent.host` or `end_points.0.id`) inside a YAML node.
+/// Numeric segments index into lists. Returns `BadValue` when the path does not exist.
+pub(crate) fn lookup_key_path<'a>(yaml: &'a Yaml, key_path: &str) -> &'a Yaml {
+    let mut node = yaml;
+    for key in key_path.split('.') {
+        node = match node {
+            Yaml::Array(_) => match key.parse::<usize>() {
+                                    Ok(idx) => &node[idx],
+                                    Err(_) => &node[key],
+                                },
+            _ => &node[key],
+        };
+        if node.is_badvalue() {
+            break;
+        }
+    }
+    node
+}
+
+///Name of the YAML type, used in error messages.
+pub(crate) fn yaml_type_name(yaml: &Yaml) -> &'static str {
+    match yaml {
+        Yaml::String(_) => "string",
+        Yaml::Integer(_) => "integer",
+        Yaml::Real(_) => "real",
+        Yaml::Boolean(_) => "boolean",
+        Yaml::Array(_) => "list",
+        Yaml::Hash(_) => "map",
+        Yaml::Null => "null",
+        Yaml::Alias(_) => "alias",
+        Yaml::BadValue => "missing value",
+    }
+}
+
+///Returns a scalar YAML value as a string. Lists, maps and null values return None.
+pub(crate) fn yaml_scalar_to_string(yaml: &Yaml) -> Option<String> {
+    match yaml {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+///Flattens a YAML node into dotted key paths and scalar values (list items are indexed by position).
+pub(crate) fn flatten_yaml(key_path: &str, yaml: &Yaml, values: &mut BTreeMap<String, String>) {
+    let join = |key: &str| if key_path.is_empty() { key.to_owned() } else { format!("{}.{}", key_path, key) };
+    match yaml {
+        Yaml::Hash(hash) => for (key, val) in hash {
+            flatten_yaml(&join(&yaml_key_to_string(key)), val, values);
+        },
+        Yaml::Array(items) => for (idx, val) in items.iter().enumerate() {
+            flatten_yaml(&join(&idx.to_string()), val, values);
+        },
+        Yaml::BadValue => (),
+        other => {
+            values.insert(key_path.to_owned(), yaml_scalar_to_string(other).unwrap_or_default());
+        },
+    }
+}
+
+///Builds a YAML map. Entries with a `BadValue` (unset) value are skipped.
+pub(crate) fn yaml_map(entries: Vec<(&str, Yaml)>) -> Yaml {
+    let mut hash = Hash::new();
+    for (key, value) in entries {
+        if !value.is_badvalue() {
+            hash.insert(Yaml::String(key.to_owned()), value);
+        }
+    }
+    Yaml::Hash(hash)
+}
+
+///YAML string, or `BadValue` when unset
+pub(crate) fn yaml_opt_str(value: &Option<String>) -> Yaml {
+    value.as_ref().map(|v| Yaml::String(v.clone())).unwrap_or(Yaml::BadValue)
+}
+
+///Serializes a YAML node as a file body (without the leading `---` document marker).
+pub(crate) fn emit_yaml(yaml: &Yaml) -> Result<String, AnyErr> {
+    let mut out = String::new();
+    YamlEmitter::new(&mut out).dump(yaml).map_err(|e| format!("Cannot serialize YAML: {}", e))?;
+    Ok(format!("{}\n", out.trim_start_matches("---").trim_start()))
+}
+
+///Deep merges `overlay` into `base`: maps are merged key by key, any other value replaces the base value.
+/// An empty (null) overlay value keeps the base map (e.g. an environment declared without keys).
+pub(crate) fn merge_yaml(base: &mut Yaml, overlay: &Yaml) {
+    match (base, overlay) {
+        (Yaml::Hash(base_hash), Yaml::Hash(overlay_hash)) => for (key, value) in overlay_hash {
+            match base_hash.get_mut(key) {
+                Some(base_value) => merge_yaml(base_value, value),
+                None => { base_hash.insert(key.clone(), value.clone()); },
+            }
+        },
+        (Yaml::Hash(_), Yaml::Null) => (),
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+///Embedded `base` configuration with the `overlay` file, if any, merged over it.
+/// Both layers are converted to the `environments:` layout before merging.
+pub(crate) fn read_layered_yaml(schema: &ConfigSchema, base: &str, overlay: Option<&Path>) -> Result<Yaml, AnyErr> {
+    let mut config = schema.with_environments_key(&parse_yaml(base)?);
+    match overlay {
+        Some(path) => {
+            merge_yaml(&mut config, &schema.with_environments_key(&read_config_file(path)?));
+            log_info!("", "{} file '{}' merged over the embedded configuration.", schema.title, path.display());
+        },
+        None => log_info!("", "No {} file found. Using the embedded configuration.", schema.title),
+    }
+    Ok(config)
+}
+
+///First document of a YAML string. An empty document is null.
+pub(crate) fn parse_yaml(content: &str) -> Result<Yaml, AnyErr> {
+    Ok(YamlLoader::load_from_str(content)?.into_iter().next().unwrap_or(Yaml::Null))
+}
+
+///Deserializes a YAML node into `T`. Errors report the key path (prefixed by `section_name`) where deserialization failed.
+pub(crate) fn deserialize_yaml<T: DeserializeOwned>(yaml: &Yaml, section_name: &str) -> Result<T, AnyErr> {
+    match serde_path_to_error::deserialize(yaml_to_json(yaml)) {
+        Ok(v) => Ok(v),
+        Err(e) => {
+            let inner_path = e.path().to_string();
+            let key_path = if inner_path == "." { section_name.to_owned() } else { format!("{}.{}", section_name, inner_path) };
+            Err(format!("Invalid value at '{}': {}", key_path, e.inner()).into())
+        },
+    }
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod config_value_tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+    use yaml_rust2::YamlLoader;
+
+    use super::{deserialize_yaml, flatten_yaml, lookup_key_path, merge_yaml, yaml_to_json};
+
+    #[test]
+    pub fn test_yaml_to_json_success(){
+        let yml = &YamlLoader::load_from_str("a: 1\nb: 1.5\nc: [x, true]\nd:\n").unwrap()[0];
+        assert_eq!(yaml_to_json(yml), json!({"a": 1, "b": 1.5, "c": ["x", true], "d": null}));
+    }
+
+    #[test]
+    pub fn test_lookup_key_path_success(){
+        let yml = &YamlLoader::load_from_str("agent:\n  host: localhost\nlist:\n  - id: one\n").unwrap()[0];
+        assert_eq!(lookup_key_path(yml, "agent.host").as_str(), Some("localhost"));
+        assert_eq!(lookup_key_path(yml, "list.0.id").as_str(), Some("one"));
+        assert!(lookup_key_path(yml, "agent.port.value").is_badvalue());
+    }
+
+    #[test]
+    pub fn test_flatten_yaml_success(){
+        let yml = &YamlLoader::load_from_str("db:\n  url: x\n  hosts: [a, b]\n  pool:\n").unwrap()[0];
+        let mut values = std::collections::BTreeMap::new();
+        flatten_yaml("", yml, &mut values);
+        let flat: Vec<(&str, &str)> = values.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        assert_eq!(flat, vec![("db.hosts.0", "a"), ("db.hosts.1", "b"), ("db.pool", ""), ("db.url", "x")]);
+    }
+
+    #[test]
+    pub fn test_merge_yaml_success(){
+        let mut base = YamlLoader::load_from_str("dev:\n  app_path: /base\n  end_points: [a, b]\n  db:\n    url: x\n    pool: 5\nqa:\n  app_path: /qa\n").unwrap().remove(0);
+        let overlay = &YamlLoader::load_from_str("dev:\n  end_points: [c]\n  db:\n    pool: 10\nqa:\nprod:\n  app_path: /prod\n").unwrap()[0];
+        merge_yaml(&mut base, overlay);
+        assert_eq!(yaml_to_json(&base), json!({"dev": {"app_path": "/base", "end_points": ["c"], "db": {"url": "x", "pool": 10}},
+                                               "qa": {"app_path": "/qa"}, "prod": {"app_path": "/prod"}}));
+    }
+
+    #[test]
+    pub fn test_deserialize_yaml_error_path(){
+        let yml = &YamlLoader::load_from_str("pool:\n  size: many\n").unwrap()[0];
+        let err = deserialize_yaml::<HashMap<String, HashMap<String, u32>>>(yml, "database").unwrap_err();
+        assert!(err.to_string().contains("'database.pool.size'"));
+    }
+}
@@ -0,0 +1,203 @@
+use bt_any_error::any_err::AnyErr;
+use bt_logger::{log_error, log_info};
+use yaml_rust2::Yaml;
+
+use crate::app_config::AppConfig;
+use crate::app_info::{AppInfo, CargoPkg, UNKNOWN_APP_NAME, UNKNOWN_APP_VERSION};
+use crate::environment::{detect_environment, file_default_environment, has_environment, EnvironmentCandidates, EnvironmentSource, ResolvedEnvironment, ENV_VAR_NAME};
+use crate::schema::ValidationMode;
+use crate::server_config::ServerConfig;
+use crate::utils::init_app_base_url;
+
+///Options of `CoreConfig::load`
+#[derive(Clone, Debug, Default)]
+pub struct LoadOptions<'a> {
+    ///Environment to run. When empty (or not defined) it is taken from the `--env`/`-e` command line flag,
+    /// the `env_var_name` environment variable, the files `environment:` key or the build profile default, in that order.
+    pub running_environment: &'a str,
+    ///Environment variable holding the running environment. None for `BT_ENV`.
+    pub env_var_name: Option<&'a str>,
+    ///Cargo metadata of the application, usually `bt_core_config::cargo_pkg!()`
+    pub cargo_pkg: CargoPkg,
+    ///Content of app-config.yml. None to use env variable or search paths.
+    pub app_embed_config: Option<&'a str>,
+    ///Content of server-config.yml. None to use env variable or search paths.
+    pub srv_embed_config: Option<&'a str>,
+    ///When true, the embedded contents are the base layer and the files (env variable or search paths), if present, are merged over them.
+    pub layered: bool,
+    pub validation: ValidationMode,
+}
+
+///Application information, application configuration and server configuration loaded for the same environment.
+#[derive(Clone, Debug)]
+pub struct CoreConfig {
+    environment: ResolvedEnvironment,
+    app_info: AppInfo,
+    app_config: AppConfig,
+    srv_config: ServerConfig,
+}
+
+impl CoreConfig {
+    ///Loads app info, app-config and server-config resolving the running environment once.
+    /// Fails if the requested environment is defined in only one of the files, if the files declare
+    /// different default environments, or if the resolved environment is missing from either file.
+    pub fn load(options: LoadOptions) -> Result<Self, AnyErr> {
+        let app_info = AppInfo::new(options.cargo_pkg, UNKNOWN_APP_NAME, UNKNOWN_APP_VERSION, "", "");
+        let (app_yaml, app_location) = match options.app_embed_config {
+            Some(base) if options.layered => AppConfig::read_yaml_layered(base)?,
+            embed_config => AppConfig::read_yaml(embed_config)?,
+        };
+        let (srv_yaml, srv_location) = match options.srv_embed_config {
+            Some(base) if options.layered => ServerConfig::read_yaml_layered(base)?,
+            embed_config => ServerConfig::read_yaml(embed_config)?,
+        };
+
+        let candidates = EnvironmentCandidates::from_process(options.running_environment, options.env_var_name.unwrap_or(ENV_VAR_NAME));
+        let environment = match resolve_shared_environment(&app_yaml, &srv_yaml, &candidates) {
+            Ok(env) => env,
+            Err(e) => {
+                log_error!("","{}",e);
+                return Err(e.into());
+            },
+        };
+        log_info!("","Using current environment '{}' (from {}).",&environment.name, &environment.source);
+
+        let app_config = AppConfig::from_yaml(app_yaml, &environment, &app_info, Some(app_info.version), options.validation, app_location)?;
+        log_info!("","Using application name '{}' (from {}).",app_config.get_app_name(), app_config.get_app_name_source());
+        let srv_config = ServerConfig::from_yaml(&srv_yaml, &environment, options.validation)?.with_location(srv_location);
+        init_app_base_url(&app_config.get_app_path());
+
+        Ok(Self { environment, app_info, app_config, srv_config })
+    }
+
+    pub fn get_environment(&self) -> &str {
+        &self.environment.name
+    }
+
+    ///Rule that chose the running environment. Useful for logging.
+    pub fn get_environment_source(&self) -> &EnvironmentSource {
+        &self.environment.source
+    }
+
+    ///`AppInfo::to_json` document with the running environment and a `runtime` section (`RuntimeInfo::to_json`).
+    /// Suitable for version/health endpoints.
+    pub fn info_json(&self) -> serde_json::Value {
+        let mut info = self.app_info.to_json(Some(&self.environment.name));
+        info["runtime"] = self.app_info.runtime_info().to_json();
+        info
+    }
+
+    pub fn app_info(&self) -> &AppInfo {
+        &self.app_info
+    }
+
+    pub fn app_config(&self) -> &AppConfig {
+        &self.app_config
+    }
+
+    pub fn server_config(&self) -> &ServerConfig {
+        &self.srv_config
+    }
+}
+
+fn resolve_shared_environment(app_yaml: &Yaml, srv_yaml: &Yaml, candidates: &EnvironmentCandidates) -> Result<ResolvedEnvironment, String> {
+    let is_defined = |env: &str| match (has_environment(app_yaml, env), has_environment(srv_yaml, env)) {
+        (true, true) => Ok(true),
+        (true, false) => Err(format!("Environment '{}' is defined in app-config but not in server-config.", env)),
+        (false, true) => Err(format!("Environment '{}' is defined in server-config but not in app-config.", env)),
+        (false, false) => Ok(false),
+    };
+
+    let file_default = match (file_default_environment(app_yaml), file_default_environment(srv_yaml)) {
+        (Some(app_default), Some(srv_default)) if app_default != srv_default => return Err(format!(
+            "Default environment differs: '{}' in app-config and '{}' in server-config.", app_default, srv_default)),
+        (Some(app_default), _) => Some(app_default),
+        (None, srv_default) => srv_default,
+    };
+
+    let resolved = detect_environment(candidates, is_defined, file_default)?;
+    match (has_environment(app_yaml, &resolved.name), has_environment(srv_yaml, &resolved.name)) {
+        (true, true) => Ok(resolved),
+        (app_has, _) => Err(format!("Environment '{}' (from {}) is not defined in {}.",
+                                    resolved.name, resolved.source, if app_has { "server-config" } else { "app-config" })),
+    }
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod core_config_tests {
+    use bt_logger::{build_logger, LogLevel, LogTarget};
+
+    use crate::app_info::CargoPkg;
+
+    use crate::environment::EnvironmentSource;
+
+    use super::{CoreConfig, LoadOptions};
+
+    const APP_CONTENT: &str = "app_name: Core\nenvironment: qa\nqa:\n  app_path: /qa\nprod:\n  app_path: /prod\nlocal:\n";
+    const SRV_CONTENT: &str = "environments:\n  qa:\n    server:\n      port: 8080\n  prod:\n    server:\n      port: 443\n";
+
+    fn options(running_environment: &str) -> LoadOptions<'_> {
+        LoadOptions{ running_environment,
+                     cargo_pkg: CargoPkg{ pkg_name: Some("core_test"), pkg_version: Some("1.2.3"), ..Default::default() },
+                     app_embed_config: Some(APP_CONTENT),
+                     srv_embed_config: Some(SRV_CONTENT),
+                     ..Default::default() }
+    }
+
+    #[test]
+    pub fn test_load_success(){
+        build_logger("BACHUETECH","CORE_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let cc = CoreConfig::load(options("prod")).unwrap();
+        assert_eq!(cc.get_environment(),"prod");
+        assert_eq!(cc.get_environment_source(),&EnvironmentSource::Explicit);
+        assert_eq!(cc.app_config().get_app_path(),"/prod");
+        assert_eq!(cc.app_config().get_version(),"1.2.3");
+        assert_eq!(cc.server_config().get_port(),443);
+        assert_eq!(cc.app_info().package_name,"core_test");
+        assert_eq!(cc.info_json()["environment"],"prod");
+        assert_eq!(cc.info_json()["runtime"]["pid"],std::process::id());
+    }
+
+    #[test]
+    pub fn test_load_default_environment(){
+        build_logger("BACHUETECH","CORE_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let cc = CoreConfig::load(options("")).unwrap();
+        assert_eq!(cc.get_environment(),"qa");
+        assert_eq!(cc.get_environment_source(),&EnvironmentSource::ConfigFile);
+        assert_eq!(cc.server_config().get_environment(),"qa");
+        assert_eq!(CoreConfig::load(options("UNKNOWN")).unwrap().get_environment(),"qa");
+        let unset_var = LoadOptions{ env_var_name: Some("BT_CORE_CONFIG_TEST_UNSET_ENV"), ..options("") };
+        assert_eq!(CoreConfig::load(unset_var).unwrap().get_environment(),"qa");
+    }
+
+    #[test]
+    pub fn test_load_layered(){
+        build_logger("BACHUETECH","CORE_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        //config/core files are merged over the embedded contents
+        let cc = CoreConfig::load(LoadOptions{ layered: true, ..options("dev") }).unwrap();
+        assert_eq!(cc.app_config().get_api_path(),"/ai/api/");
+        assert_eq!(cc.server_config().get_port(),23332);
+        let cc = CoreConfig::load(LoadOptions{ layered: true, ..options("prod") }).unwrap();
+        assert_eq!(cc.app_config().get_app_path(),"/prod");
+        assert_eq!(cc.server_config().get_tcp_listener(),"127.0.0.1:23333");
+    }
+
+    #[test]
+    pub fn test_load_environment_mismatch(){
+        build_logger("BACHUETECH","CORE_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let err = CoreConfig::load(options("local")).unwrap_err();
+        assert!(err.to_string().contains("defined in app-config but not in server-config"));
+    }
+
+    #[test]
+    pub fn test_load_repository_files(){
+        build_logger("BACHUETECH","CORE_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        assert_eq!(CoreConfig::load(LoadOptions{ running_environment: "dev", ..Default::default() }).unwrap().server_config().get_port(),23332);
+        //app-config defaults to 'devNone', which server-config does not define
+        assert!(CoreConfig::load(LoadOptions::default()).is_err());
+    }
+}
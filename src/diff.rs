@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bt_any_error::any_err::AnyErr;
+use yaml_rust2::Yaml;
+
+use crate::app_config::AppConfig;
+use crate::app_info::AppInfo;
+use crate::environment::{has_environment, ResolvedEnvironment};
+use crate::schema::ValidationMode;
+use crate::server_config::ServerConfig;
+
+///Differences between two sets of effective values, keyed by dotted key path (e.g. `agent.port`, `end_points.chat`).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConfigDiff {
+    ///Keys only present in the second configuration
+    pub added: BTreeMap<String, String>,
+    ///Keys only present in the first configuration
+    pub removed: BTreeMap<String, String>,
+    ///Keys present in both with different values: (first, second)
+    pub changed: BTreeMap<String, (String, String)>,
+}
+
+impl ConfigDiff {
+    ///Compares two sets of effective values
+    pub fn between(before: &BTreeMap<String, String>, after: &BTreeMap<String, String>) -> Self {
+        let mut diff = Self::default();
+        for (key, old) in before {
+            match after.get(key) {
+                None => { diff.removed.insert(key.clone(), old.clone()); },
+                Some(new) if new != old => { diff.changed.insert(key.clone(), (old.clone(), new.clone())); },
+                Some(_) => (),
+            }
+        }
+        for (key, new) in after {
+            if !before.contains_key(key) {
+                diff.added.insert(key.clone(), new.clone());
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    ///Merges another diff (e.g. server differences into app differences)
+    pub fn extend(&mut self, other: ConfigDiff) {
+        self.added.extend(other.added);
+        self.removed.extend(other.removed);
+        self.changed.extend(other.changed);
+    }
+}
+
+///One line per difference: `+ key: value`, `- key: value` or `~ key: old -> new`
+impl fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = Vec::new();
+        for (key, value) in &self.removed {
+            lines.push(format!("- {}: {}", key, value));
+        }
+        for (key, (old, new)) in &self.changed {
+            lines.push(format!("~ {}: {} -> {}", key, old, new));
+        }
+        for (key, value) in &self.added {
+            lines.push(format!("+ {}: {}", key, value));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+///Differences between two loaded application configurations (e.g. before and after a reload).
+pub fn diff_app_configs(before: &AppConfig, after: &AppConfig) -> ConfigDiff {
+    ConfigDiff::between(&before.effective_values(), &after.effective_values())
+}
+
+///Differences between two loaded server configurations
+pub fn diff_server_configs(before: &ServerConfig, after: &ServerConfig) -> ConfigDiff {
+    ConfigDiff::between(&before.effective_values(), &after.effective_values())
+}
+
+///Differences between two environments of the app-config file. Unlike `AppConfig::new`, unknown environments are an error
+/// and the environment is never taken from the command line or env variables. The global App Base URL is not initialized.
+/// embed_config: Content of the YML config file. None to use env variable or search paths.
+pub fn diff_app_environments(env_a: &str, env_b: &str, app_info: &AppInfo, embed_config: Option<&str>) -> Result<ConfigDiff, AnyErr> {
+    let (app_config, location) = AppConfig::read_yaml(embed_config)?;
+    require_environments(&app_config, &[env_a, env_b])?;
+    let before = AppConfig::from_yaml(app_config.clone(), &ResolvedEnvironment::explicit(env_a), app_info, Some(app_info.version), ValidationMode::Lenient, location.clone())?;
+    let after = AppConfig::from_yaml(app_config, &ResolvedEnvironment::explicit(env_b), app_info, Some(app_info.version), ValidationMode::Lenient, location)?;
+    Ok(diff_app_configs(&before, &after))
+}
+
+///Differences between two environments of the server-config file. Unlike `ServerConfig::new`, unknown environments are an error
+/// and the environment is never taken from the command line or env variables.
+/// embed_config: Content of the YML config file. None to use env variable or search paths.
+pub fn diff_server_environments(env_a: &str, env_b: &str, embed_config: Option<&str>) -> Result<ConfigDiff, AnyErr> {
+    let (srv_config, _) = ServerConfig::read_yaml(embed_config)?;
+    require_environments(&srv_config, &[env_a, env_b])?;
+    let before = ServerConfig::from_yaml(&srv_config, &ResolvedEnvironment::explicit(env_a), ValidationMode::Lenient)?;
+    let after = ServerConfig::from_yaml(&srv_config, &ResolvedEnvironment::explicit(env_b), ValidationMode::Lenient)?;
+    Ok(diff_server_configs(&before, &after))
+}
+
+fn require_environments(config: &Yaml, environments: &[&str]) -> Result<(), AnyErr> {
+    match environments.iter().find(|env| !has_environment(config, env)) {
+        Some(missing) => Err(format!("Environment '{}' not found.", missing).into()),
+        None => Ok(()),
+    }
+}
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+#[allow(deprecated)]
+mod diff_tests {
+    use bt_logger::{build_logger, LogLevel, LogTarget};
+
+    use crate::app_info::AppInfo;
+
+    use super::{diff_app_environments, diff_server_environments};
+
+    #[test]
+    pub fn test_diff_app_environments(){
+        build_logger("BACHUETECH","DIFF",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let app_info = AppInfo::get_app_info("AppName", "default_version", "Bachuetech", "Core Test");
+        let diff = diff_app_environments("dev", "jeremy_dev", &app_info, None).unwrap();
+        assert_eq!(diff.changed["app_path"], ("/app".to_owned(), "/jeremy".to_owned()));
+        assert_eq!(diff.added["agent.port"], "23332");
+        assert_eq!(diff.added["end_points.prompt"], "/prompt");
+        assert!(diff.removed.is_empty());
+        assert!(diff.to_string().contains("~ app_path: /app -> /jeremy"));
+        assert!(diff_app_environments("dev", "dev", &app_info, None).unwrap().is_empty());
+        assert!(diff_app_environments("dev", "UNKNOWN", &app_info, None).is_err());
+    }
+
+    #[test]
+    pub fn test_diff_server_environments(){
+        build_logger("BACHUETECH","DIFF",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        const YML_CONTENT: &str = include_str!("../config/core/server-config.yml");
+        let diff = diff_server_environments("dev", "prod", Some(YML_CONTENT)).unwrap();
+        assert_eq!(diff.changed["server.port"], ("23332".to_owned(), "23333".to_owned()));
+        assert_eq!(diff.changed.len(), 3);
+        assert!(diff_server_environments("dev", "UNKNOWN", Some(YML_CONTENT)).is_err());
+        assert!(diff_server_environments("dev", "UNKNOWN", None).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use bt_any_error::any_err::AnyErr;
+use bt_yaml_utils::get_yaml_from_string;
+
+use crate::environment::{file_default_environment, has_environment};
+use crate::schema::{ValidationMode, APP_CONFIG_SCHEMA, SRV_CONFIG_SCHEMA};
+
+///Content of app-config.yml and server-config.yml embedded in the binary. Built with `embed_core_config!`.
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddedConfig {
+    pub app_config: &'static str,
+    pub srv_config: &'static str,
+}
+
+///Environment variable set by `build::check_core_config` with the folders it validated, separated by `:`
+pub const CHECKED_DIRS_ENV_VAR: &str = "BT_CORE_CONFIG_CHECKED";
+
+///Embeds `app-config.yml` and `server-config.yml` of a folder (relative to the crate root) at compile time.
+/// The folder must be validated by `bt_core_config::build::check_core_config` in the build script of the same crate;
+/// otherwise compilation fails, so unvalidated configuration is never embedded. A missing file also fails compilation.
+/// `embed_core_config!(unchecked "dir")` skips the build script requirement (e.g. in tests).
+///
+/// build.rs (with `bt_core_config` in `[build-dependencies]`):
+/// `fn main() { bt_core_config::build::check_core_config("config/core", &["dev", "prod"]); }`
+///
+/// main.rs:
+/// `let embedded = bt_core_config::embed_core_config!("config/core");`
+#[macro_export]
+macro_rules! embed_core_config {
+    ($dir:literal) => {{
+        const _: () = assert!(
+            $crate::embedded::is_checked_dir(
+                env!("BT_CORE_CONFIG_CHECKED", "embed_core_config!: call bt_core_config::build::check_core_config from build.rs"), $dir),
+            concat!("embed_core_config!: '", $dir, "' is not validated by bt_core_config::build::check_core_config in build.rs"));
+        $crate::embed_core_config!(unchecked $dir)
+    }};
+    (unchecked $dir:literal) => {
+        $crate::embedded::EmbeddedConfig {
+            app_config: include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $dir, "/app-config.yml")),
+            srv_config: include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $dir, "/server-config.yml")),
+        }
+    };
+}
+
+///True when `dir` is one of the `:` separated folders of `checked_dirs`. Evaluated at compile time by `embed_core_config!`.
+#[doc(hidden)]
+pub const fn is_checked_dir(checked_dirs: &str, dir: &str) -> bool {
+    let (list, dir) = (checked_dirs.as_bytes(), dir.as_bytes());
+    let mut start = 0;
+    while start <= list.len() {
+        let mut end = start;
+        while end < list.len() && list[end] != b':' {
+            end += 1;
+        }
+        if end - start == dir.len() {
+            let mut idx = 0;
+            while idx < dir.len() && list[start + idx] == dir[idx] {
+                idx += 1;
+            }
+            if idx == dir.len() {
+                return true;
+            }
+        }
+        start = end + 1;
+    }
+    false
+}
+
+impl EmbeddedConfig {
+    ///Validates both files. See `validate_config_pair`.
+    pub fn validate(&self, environments: &[&str], validation: ValidationMode) -> Result<(), AnyErr> {
+        validate_config_pair(self.app_config, self.srv_config, environments, validation)
+    }
+}
+
+///Validates an app-config and server-config pair: every environment of each file against its schema,
+/// each of `environments` defined in both files, and the default `environment:` of the files defined in both and identical.
+/// The error lists every problem found, one per line.
+pub fn validate_config_pair(app_config: &str, srv_config: &str, environments: &[&str], validation: ValidationMode) -> Result<(), AnyErr> {
+    let app_yaml = get_yaml_from_string(app_config).map_err(|e| format!("app-config: invalid YAML: {}", e))?;
+    let srv_yaml = get_yaml_from_string(srv_config).map_err(|e| format!("server-config: invalid YAML: {}", e))?;
+
+    let mut problems = Vec::new();
+    for (schema, yaml) in [(&APP_CONFIG_SCHEMA, &app_yaml), (&SRV_CONFIG_SCHEMA, &srv_yaml)] {
+        for env in schema.environments(yaml) {
+            if let Err(e) = schema.check(yaml, &env, validation) {
+                problems.push(e.to_string());
+            }
+        }
+    }
+
+    let app_default = file_default_environment(&app_yaml);
+    let srv_default = file_default_environment(&srv_yaml);
+    if let (Some(app_env), Some(srv_env)) = (app_default, srv_default) && app_env != srv_env {
+        problems.push(format!("Default environment differs: '{}' in app-config and '{}' in server-config.", app_env, srv_env));
+    }
+    let defaults = app_default.into_iter().chain(srv_default);
+    for env in environments.iter().copied().chain(defaults) {
+        for (title, yaml) in [("app-config", &app_yaml), ("server-config", &srv_yaml)] {
+            let problem = format!("Environment '{}' is not defined in {}.", env, title);
+            if !has_environment(yaml, env) && !problems.contains(&problem) {
+                problems.push(problem);
+            }
+        }
+    }
+
+    if problems.is_empty() { Ok(()) } else { Err(problems.join("\n").into()) }
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod embedded_tests {
+    use crate::schema::ValidationMode;
+
+    use super::{is_checked_dir, EmbeddedConfig};
+
+    #[test]
+    pub fn test_embed_core_config(){
+        let embedded = crate::embed_core_config!(unchecked "config/core");
+        assert_eq!(embedded.app_config, include_str!("../config/core/app-config.yml"));
+        let err = embedded.validate(&["dev"], ValidationMode::Lenient).unwrap_err().to_string();
+        assert_eq!(err, "Environment 'devNone' is not defined in server-config.");
+    }
+
+    #[test]
+    pub fn test_is_checked_dir(){
+        const { assert!(is_checked_dir("config/core:config/other", "config/other")) };
+        assert!(is_checked_dir("config/core", "config/core"));
+        assert!(!is_checked_dir("config/core", "config"));
+        assert!(!is_checked_dir("config/core2", "config/core"));
+        assert!(!is_checked_dir("", "config/core"));
+    }
+
+    #[test]
+    pub fn test_validate_errors(){
+        let embedded = EmbeddedConfig{ app_config: "environment: qa\nqa:\n  api_path: api\n", srv_config: "qa:\n  server:\n    port: 80\n" };
+        assert!(embedded.validate(&["qa"], ValidationMode::Lenient).unwrap_err().to_string().contains("qa.api_path"));
+        let embedded = EmbeddedConfig{ app_config: "qa:\n  api_path: /api\n", srv_config: "qa:\n  server:\n    prot: 80\n" };
+        assert!(embedded.validate(&["qa"], ValidationMode::Lenient).is_ok());
+        assert!(embedded.validate(&["qa"], ValidationMode::Strict).is_err());
+        let err = embedded.validate(&["qa", "prod"], ValidationMode::Lenient).unwrap_err().to_string();
+        assert!(err.contains("'prod' is not defined in app-config") && err.contains("'prod' is not defined in server-config"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt;
+
+use bt_logger::{log_info, log_warning};
+use yaml_rust2::Yaml;
+
+use crate::schema::environment_block;
+
+///Environment used when neither the caller nor the configuration file provide a valid one
+#[cfg(debug_assertions)]
+pub const DEFAULT_RUN_ENV: &str = "dev";
+#[cfg(not(debug_assertions))]
+pub const DEFAULT_RUN_ENV: &str = "prod";
+
+///Default name of the environment variable holding the running environment
+pub const ENV_VAR_NAME: &str = "BT_ENV";
+
+///Rule that chose the running environment, in order of precedence
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvironmentSource {
+    ///Passed by the caller
+    Explicit,
+    ///`--env <name>`, `--env=<name>` or `-e <name>` command line flag
+    CommandLine,
+    ///Environment variable. Holds the variable name.
+    EnvVar(String),
+    ///`environment:` key of the configuration file
+    ConfigFile,
+    ///`dev` in debug builds, `prod` in release builds
+    BuildDefault,
+}
+
+impl fmt::Display for EnvironmentSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvironmentSource::Explicit => write!(f, "explicit argument"),
+            EnvironmentSource::CommandLine => write!(f, "command line flag --env"),
+            EnvironmentSource::EnvVar(name) => write!(f, "environment variable {}", name),
+            EnvironmentSource::ConfigFile => write!(f, "'environment' key of the configuration file"),
+            EnvironmentSource::BuildDefault => write!(f, "build profile default"),
+        }
+    }
+}
+
+///Running environment and the rule that chose it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolvedEnvironment {
+    pub name: String,
+    pub source: EnvironmentSource,
+}
+
+impl ResolvedEnvironment {
+    ///Environment chosen by the caller
+    pub fn explicit(name: &str) -> Self {
+        Self { name: name.to_owned(), source: EnvironmentSource::Explicit }
+    }
+}
+
+///Values available to choose the running environment. Empty values are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct EnvironmentCandidates {
+    pub explicit: Option<String>,
+    pub command_line: Option<String>,
+    ///(variable name, value)
+    pub env_var: Option<(String, String)>,
+}
+
+impl EnvironmentCandidates {
+    ///Only the caller's environment. The command line and env variables are not read.
+    pub fn explicit(explicit: &str) -> Self {
+        Self { explicit: Some(explicit.to_owned()), ..Default::default() }
+    }
+
+    ///Collects the candidates from the caller, the process arguments and the `env_var_name` variable.
+    pub fn from_process(explicit: &str, env_var_name: &str) -> Self {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        Self {
+            explicit: Some(explicit.to_owned()),
+            command_line: env_from_args(&args),
+            env_var: std::env::var(env_var_name).ok().map(|v| (env_var_name.to_owned(), v)),
+        }
+    }
+}
+
+///Value of `--env <name>`, `--env=<name>` or `-e <name>`. The last occurrence wins.
+pub fn env_from_args(args: &[String]) -> Option<String> {
+    let mut env = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--" {
+            break;
+        }
+        if arg == "--env" || arg == "-e" {
+            env = iter.next().cloned();
+        } else if let Some(value) = arg.strip_prefix("--env=") {
+            env = Some(value.to_owned());
+        }
+    }
+    env
+}
+
+///Chooses the running environment: explicit argument, command line flag, environment variable, file default, build default.
+/// Candidates for which `is_defined` returns false are skipped with a warning. `is_defined` may reject a candidate with an error.
+pub fn detect_environment(candidates: &EnvironmentCandidates, is_defined: impl Fn(&str) -> Result<bool, String>, file_default: Option<&str>) -> Result<ResolvedEnvironment, String> {
+    let mut ordered = vec![
+        (candidates.explicit.clone(), EnvironmentSource::Explicit),
+        (candidates.command_line.clone(), EnvironmentSource::CommandLine),
+    ];
+    if let Some((var_name, value)) = &candidates.env_var {
+        ordered.push((Some(value.clone()), EnvironmentSource::EnvVar(var_name.clone())));
+    }
+
+    for (value, source) in ordered {
+        let name = value.unwrap_or_default().trim().to_owned();
+        if name.is_empty() {
+            continue;
+        }
+        if is_defined(&name)? {
+            return Ok(ResolvedEnvironment{ name, source });
+        }
+        log_warning!("","Environment '{}' from {} is not defined. Trying the next rule.",name, source);
+    }
+
+    Ok(match file_default.map(|d| d.trim()).filter(|d| !d.is_empty()) {
+        Some(default_env) => ResolvedEnvironment{ name: default_env.to_owned(), source: EnvironmentSource::ConfigFile },
+        None => ResolvedEnvironment{ name: DEFAULT_RUN_ENV.to_owned(), source: EnvironmentSource::BuildDefault },
+    })
+}
+
+///`environment:` key of a configuration file
+pub fn file_default_environment(config: &Yaml) -> Option<&str> {
+    config["environment"].as_str()
+}
+
+///True if the configuration file defines `environment` (an empty block counts as defined)
+pub fn has_environment(config: &Yaml, environment: &str) -> bool {
+    !environment.trim().is_empty() && !environment_block(config, environment).is_badvalue()
+}
+
+///Resolves the running environment of a single file. Unknown candidates fall back to the next rule.
+pub(crate) fn resolve_environment(config: &Yaml, candidates: &EnvironmentCandidates) -> ResolvedEnvironment {
+    let resolved = detect_environment(candidates, |env| Ok(has_environment(config, env)), file_default_environment(config))
+        .unwrap_or(ResolvedEnvironment{ name: DEFAULT_RUN_ENV.to_owned(), source: EnvironmentSource::BuildDefault });
+    log_info!("","Using current environment '{}' (from {}).",&resolved.name, &resolved.source);
+    resolved
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod environment_tests {
+    use super::{detect_environment, env_from_args, EnvironmentCandidates, EnvironmentSource, DEFAULT_RUN_ENV};
+
+    fn candidates(explicit: &str, command_line: Option<&str>, env_var: Option<&str>) -> EnvironmentCandidates {
+        EnvironmentCandidates{ explicit: Some(explicit.to_owned()),
+                               command_line: command_line.map(|s| s.to_owned()),
+                               env_var: env_var.map(|v| ("APP_ENV".to_owned(), v.to_owned())) }
+    }
+
+    #[test]
+    pub fn test_env_from_args(){
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        assert_eq!(env_from_args(&args(&["--env", "qa"])), Some("qa".to_owned()));
+        assert_eq!(env_from_args(&args(&["-v", "--env=prod"])), Some("prod".to_owned()));
+        assert_eq!(env_from_args(&args(&["-e", "dev", "--", "--env", "x"])), Some("dev".to_owned()));
+        assert_eq!(env_from_args(&args(&["--environment", "dev"])), None);
+    }
+
+    #[test]
+    pub fn test_detect_environment_precedence(){
+        let defined = |env: &str| Ok(["qa", "prod", "dev"].contains(&env));
+        let r = detect_environment(&candidates("prod", Some("qa"), Some("dev")), defined, Some("dev")).unwrap();
+        assert_eq!((r.name.as_str(), r.source), ("prod", EnvironmentSource::Explicit));
+        let r = detect_environment(&candidates("", Some("qa"), Some("dev")), defined, None).unwrap();
+        assert_eq!((r.name.as_str(), r.source), ("qa", EnvironmentSource::CommandLine));
+        let r = detect_environment(&candidates("UNKNOWN", None, Some("dev")), defined, None).unwrap();
+        assert_eq!((r.name.as_str(), r.source), ("dev", EnvironmentSource::EnvVar("APP_ENV".to_owned())));
+        let r = detect_environment(&candidates("", None, None), defined, Some("qa")).unwrap();
+        assert_eq!((r.name.as_str(), r.source), ("qa", EnvironmentSource::ConfigFile));
+        let r = detect_environment(&candidates("", None, None), defined, None).unwrap();
+        assert_eq!((r.name.as_str(), r.source), (DEFAULT_RUN_ENV, EnvironmentSource::BuildDefault));
+    }
+
+    #[test]
+    pub fn test_detect_environment_rejected(){
+        let err = detect_environment(&candidates("qa", None, None), |_| Err("mismatch".to_owned()), None).unwrap_err();
+        assert_eq!(err, "mismatch");
+    }
+}
@@ -1,4 +1,18 @@
 pub mod app_config;
 pub mod server_config;
 pub mod app_info;
+pub mod app_name;
 pub mod utils;
+pub mod schema;
+pub mod diff;
+pub mod environment;
+pub mod core_config;
+pub mod scaffold;
+pub mod embedded;
+pub mod build;
+pub mod config_file;
+pub mod version;
+pub mod migration;
+pub mod runtime_info;
+pub mod app_dirs;
+mod config_value;
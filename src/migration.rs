@@ -0,0 +1,142 @@
+use std::fs;
+use std::path::Path;
+
+use bt_any_error::any_err::AnyErr;
+use bt_logger::log_warning;
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::Yaml;
+
+use crate::config_file::read_config_file;
+use crate::config_value::emit_yaml;
+use crate::schema::{ConfigSchema, ENVIRONMENTS_KEY};
+
+///Top level key with the layout version of a configuration file. Files without it are version 1.
+pub const CONFIG_VERSION_KEY: &str = "config_version";
+///Layout version written by this crate
+pub const CURRENT_CONFIG_VERSION: i64 = 2;
+
+///Upgrade step of a configuration file from `from_version` to the next version.
+pub struct Migration {
+    pub from_version: i64,
+    pub description: &'static str,
+    ///Applies the step. Returns false when the file did not need it.
+    pub apply: fn(&ConfigSchema, &mut Yaml) -> bool,
+}
+
+///Every migration step, in version order
+pub const MIGRATIONS: &[Migration] = &[
+    Migration{ from_version: 1, description: "environment blocks moved under 'environments:'", apply: move_environments },
+];
+
+fn move_environments(schema: &ConfigSchema, config: &mut Yaml) -> bool {
+    if config[ENVIRONMENTS_KEY].as_hash().is_some() {
+        return false;
+    }
+    *config = schema.with_environments_key(config);
+    true
+}
+
+///Upgrades `config` to `CURRENT_CONFIG_VERSION` and sets its `config_version:`.
+/// Returns the descriptions of the steps that changed the file. Files newer than this crate are an error.
+pub fn migrate(schema: &ConfigSchema, config: &mut Yaml) -> Result<Vec<&'static str>, AnyErr> {
+    if config.as_hash().is_none() {
+        return Ok(Vec::new());
+    }
+    let version = match &config[CONFIG_VERSION_KEY] {
+        Yaml::BadValue | Yaml::Null => 1,
+        Yaml::Integer(v) if (1..=CURRENT_CONFIG_VERSION).contains(v) => *v,
+        Yaml::Integer(v) if *v > CURRENT_CONFIG_VERSION => return Err(format!(
+            "{} {}: {} is newer than the supported version {}. Upgrade bt_core_config.", schema.title, CONFIG_VERSION_KEY, v, CURRENT_CONFIG_VERSION).into()),
+        other => return Err(format!("{} {}: invalid value {:?}.", schema.title, CONFIG_VERSION_KEY, other).into()),
+    };
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.from_version >= version) {
+        if (migration.apply)(schema, config) {
+            applied.push(migration.description);
+        }
+    }
+    if version < CURRENT_CONFIG_VERSION && let Some(hash) = config.as_hash() {
+        let mut upgraded = Hash::new();
+        upgraded.insert(Yaml::String(CONFIG_VERSION_KEY.to_owned()), Yaml::Integer(CURRENT_CONFIG_VERSION));
+        upgraded.extend(hash.iter().filter(|(k, _)| k.as_str() != Some(CONFIG_VERSION_KEY)).map(|(k, v)| (k.clone(), v.clone())));
+        *config = Yaml::Hash(upgraded);
+    }
+    Ok(applied)
+}
+
+///Migrates a configuration already read, logging a warning with the steps applied.
+pub(crate) fn upgrade_config(schema: &ConfigSchema, mut config: Yaml) -> Result<Yaml, AnyErr> {
+    let applied = migrate(schema, &mut config)?;
+    if !applied.is_empty() {
+        log_warning!("", "{} was migrated to {} {}: {}. Use migrate_file to update the file.",
+                     schema.title, CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION, applied.join("; "));
+    }
+    Ok(config)
+}
+
+///Migrates a configuration file in place. The original file is kept with a `.bak` extension when a step was applied.
+/// Comments are not preserved. Returns the descriptions of the steps applied.
+pub fn migrate_file(schema: &ConfigSchema, path: &Path) -> Result<Vec<&'static str>, AnyErr> {
+    let mut config = read_config_file(path)?;
+    let applied = migrate(schema, &mut config)?;
+    if !applied.is_empty() {
+        let mut backup_name = path.file_name().unwrap_or_default().to_owned();
+        backup_name.push(".bak");
+        let backup = path.with_file_name(backup_name);
+        fs::copy(path, &backup).map_err(|e| format!("Cannot write '{}': {}", backup.display(), e))?;
+        fs::write(path, emit_yaml(&config)?).map_err(|e| format!("Cannot write '{}': {}", path.display(), e))?;
+    }
+    Ok(applied)
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod migration_tests {
+    use yaml_rust2::YamlLoader;
+
+    use crate::schema::{APP_CONFIG_SCHEMA, SRV_CONFIG_SCHEMA};
+
+    use super::{migrate, migrate_file, CURRENT_CONFIG_VERSION};
+
+    #[test]
+    pub fn test_migrate_legacy_layout(){
+        let mut config = YamlLoader::load_from_str("app_name: Legacy\nenvironment: dev\ndev:\n  app_path: /dev\nempty:\n").unwrap().remove(0);
+        let applied = migrate(&APP_CONFIG_SCHEMA, &mut config).unwrap();
+        assert_eq!(applied, vec!["environment blocks moved under 'environments:'"]);
+        assert_eq!(config["config_version"].as_i64(), Some(CURRENT_CONFIG_VERSION));
+        assert_eq!(config["environments"]["dev"]["app_path"].as_str(), Some("/dev"));
+        assert!(config["environments"]["empty"].is_null());
+        assert_eq!(config["environment"].as_str(), Some("dev"));
+        assert!(migrate(&APP_CONFIG_SCHEMA, &mut config).unwrap().is_empty());
+    }
+
+    #[test]
+    pub fn test_migrate_errors(){
+        let mut config = YamlLoader::load_from_str("config_version: 9\ndev:\n").unwrap().remove(0);
+        assert!(migrate(&SRV_CONFIG_SCHEMA, &mut config).unwrap_err().to_string().contains("newer than the supported version"));
+        let mut config = YamlLoader::load_from_str("config_version: two\n").unwrap().remove(0);
+        assert!(migrate(&SRV_CONFIG_SCHEMA, &mut config).is_err());
+    }
+
+    #[test]
+    pub fn test_migrate_file(){
+        let dir = std::env::temp_dir().join(format!("bt_core_config_migration_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("server-config.yml");
+        std::fs::write(&path, "dev:\n  server:\n    port: 8080\n").unwrap();
+        assert_eq!(migrate_file(&SRV_CONFIG_SCHEMA, &path).unwrap().len(), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "config_version: 2\nenvironments:\n  dev:\n    server:\n      port: 8080\n");
+        assert!(dir.join("server-config.yml.bak").exists());
+        assert!(migrate_file(&SRV_CONFIG_SCHEMA, &path).unwrap().is_empty());
+        //The backup keeps the original extension
+        let path = dir.join("app-config.yaml");
+        std::fs::write(&path, "dev:\n  api_path: /api/\n").unwrap();
+        assert_eq!(migrate_file(&APP_CONFIG_SCHEMA, &path).unwrap().len(), 1);
+        assert!(dir.join("app-config.yaml.bak").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::{env, thread};
+
+use serde_json::{json, Value};
+
+use crate::utils::format_utc;
+
+static PROCESS_START: OnceLock<(SystemTime, Instant)> = OnceLock::new();
+///Clock ticks per second of /proc times (`USER_HZ`, fixed at 100 by the Linux user space ABI)
+const PROC_TICKS_PER_SEC: u64 = 100;
+
+///Records the start time of the process. Called by `AppInfo::new` and `RuntimeInfo::collect`; only the first call counts.
+/// On Linux it is read from /proc/self/stat. Elsewhere it is the time of the first call (config initialization).
+pub fn mark_start() {
+    PROCESS_START.get_or_init(|| {
+        let (now, now_instant) = (SystemTime::now(), Instant::now());
+        proc_start_time()
+            .and_then(|start| now.duration_since(start).ok())
+            .and_then(|elapsed| now_instant.checked_sub(elapsed).map(|instant| (now - elapsed, instant)))
+            .unwrap_or((now, now_instant))
+    });
+}
+
+///Process start time from the boot time of /proc/stat and the start ticks of /proc/self/stat (Linux)
+fn proc_start_time() -> Option<SystemTime> {
+    let boot_secs = parse_boot_time(&read_trimmed("/proc/stat")?)?;
+    let start_ticks = parse_start_ticks(&read_trimmed("/proc/self/stat")?)?;
+    let start = Duration::from_secs(boot_secs) + Duration::from_millis(start_ticks * 1000 / PROC_TICKS_PER_SEC);
+    UNIX_EPOCH.checked_add(start)
+}
+
+///Facts about the host and process the application runs on. Values that cannot be read on the platform are None.
+#[derive(Clone, Debug)]
+pub struct RuntimeInfo {
+    pub hostname: Option<String>,
+    pub pid: u32,
+    pub user: Option<String>,
+    ///`linux`, `macos`, `windows`...
+    pub os: &'static str,
+    ///Distribution name (e.g. `Ubuntu 24.04 LTS`) from /etc/os-release
+    pub os_release: Option<String>,
+    pub kernel_release: Option<String>,
+    pub cpu_count: usize,
+    ///Memory available for new processes, in bytes
+    pub available_memory: Option<u64>,
+    ///Container runtime (`docker`, `podman`, `kubernetes`, `containerd` or `lxc`) when running in a container
+    pub container: Option<&'static str>,
+    pub start_time: SystemTime,
+    start_instant: Instant,
+}
+
+impl RuntimeInfo {
+    ///Reads the runtime facts. Memory and uptime change over time; call again to refresh them.
+    pub fn collect() -> Self {
+        mark_start();
+        let (start_time, start_instant) = *PROCESS_START.get().expect("start time is initialized");
+        let cgroup = read_trimmed("/proc/1/cgroup").unwrap_or_default();
+        Self {
+            hostname: read_trimmed("/proc/sys/kernel/hostname")
+                .or_else(|| read_trimmed("/etc/hostname"))
+                .or_else(|| non_empty_var("HOSTNAME"))
+                .or_else(|| non_empty_var("COMPUTERNAME")),
+            pid: std::process::id(),
+            user: non_empty_var("USER")
+                .or_else(|| non_empty_var("LOGNAME"))
+                .or_else(|| non_empty_var("USERNAME"))
+                .or_else(current_uid_name),
+            os: env::consts::OS,
+            os_release: read_trimmed("/etc/os-release").and_then(|c| parse_os_release(&c)),
+            kernel_release: read_trimmed("/proc/sys/kernel/osrelease"),
+            cpu_count: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            available_memory: read_trimmed("/proc/meminfo").and_then(|c| parse_mem_available(&c)),
+            container: detect_container(Path::new("/.dockerenv").exists(), Path::new("/run/.containerenv").exists(),
+                                        non_empty_var("KUBERNETES_SERVICE_HOST").is_some(), &cgroup),
+            start_time,
+            start_instant,
+        }
+    }
+
+    ///Time since the process started (see `mark_start`)
+    pub fn uptime(&self) -> Duration {
+        self.start_instant.elapsed()
+    }
+
+    ///Document for logs and version endpoints. Every key is present; unknown values are null.
+    pub fn to_json(&self) -> Value {
+        let start_secs = self.start_time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        json!({
+            "hostname": self.hostname,
+            "pid": self.pid,
+            "user": self.user,
+            "os": self.os,
+            "os_release": self.os_release,
+            "kernel_release": self.kernel_release,
+            "cpu_count": self.cpu_count,
+            "available_memory": self.available_memory,
+            "container": self.container,
+            "start_time": format_utc(start_secs),
+            "uptime_secs": self.uptime().as_secs(),
+        })
+    }
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok().map(|c| c.trim().to_owned()).filter(|c| !c.is_empty())
+}
+
+fn non_empty_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+///Name of the real user id from /etc/passwd (Linux)
+fn current_uid_name() -> Option<String> {
+    let status = read_trimmed("/proc/self/status")?;
+    let uid = status.lines().find_map(|l| l.strip_prefix("Uid:"))?.split_whitespace().next()?.to_owned();
+    read_trimmed("/etc/passwd")?.lines()
+        .map(|l| l.split(':').collect::<Vec<&str>>())
+        .find(|fields| fields.get(2) == Some(&uid.as_str()))
+        .map(|fields| fields[0].to_owned())
+        .or(Some(uid))
+}
+
+///`PRETTY_NAME` of /etc/os-release
+fn parse_os_release(content: &str) -> Option<String> {
+    content.lines()
+        .find_map(|l| l.strip_prefix("PRETTY_NAME="))
+        .map(|v| v.trim_matches('"').to_owned())
+}
+
+///`MemAvailable` of /proc/meminfo, in bytes
+fn parse_mem_available(content: &str) -> Option<u64> {
+    let kb = content.lines().find_map(|l| l.strip_prefix("MemAvailable:"))?.split_whitespace().next()?;
+    kb.parse::<u64>().ok().map(|kb| kb * 1024)
+}
+
+///`btime` of /proc/stat: boot time in seconds since the epoch
+fn parse_boot_time(content: &str) -> Option<u64> {
+    content.lines().find_map(|l| l.strip_prefix("btime "))?.trim().parse().ok()
+}
+
+///Field 22 (`starttime`) of /proc/<pid>/stat: clock ticks between boot and the process start.
+/// The command name (field 2) may contain spaces and parentheses, so fields are counted after its last ')'.
+fn parse_start_ticks(content: &str) -> Option<u64> {
+    let (_, fields) = content.rsplit_once(')')?;
+    fields.split_whitespace().nth(19)?.parse().ok()
+}
+
+fn detect_container(dockerenv: bool, containerenv: bool, kubernetes_env: bool, cgroup: &str) -> Option<&'static str> {
+    if kubernetes_env || cgroup.contains("kubepods") {
+        Some("kubernetes")
+    } else if dockerenv || cgroup.contains("docker") {
+        Some("docker")
+    } else if containerenv || cgroup.contains("libpod") {
+        Some("podman")
+    } else if cgroup.contains("containerd") {
+        Some("containerd")
+    } else if cgroup.contains("lxc") {
+        Some("lxc")
+    } else {
+        None
+    }
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod runtime_info_tests {
+    use super::{detect_container, parse_boot_time, parse_mem_available, parse_os_release, parse_start_ticks, RuntimeInfo};
+
+    #[test]
+    pub fn test_collect(){
+        let info = RuntimeInfo::collect();
+        assert_eq!(info.pid, std::process::id());
+        assert!(info.cpu_count >= 1);
+        let doc = info.to_json();
+        assert!(doc["start_time"].as_str().unwrap().ends_with('Z'));
+        assert!(doc.as_object().unwrap().contains_key("container"));
+        assert_eq!(RuntimeInfo::collect().start_time, info.start_time);
+        #[cfg(target_os = "linux")]
+        assert!(info.start_time <= std::time::SystemTime::now());
+    }
+
+    #[test]
+    pub fn test_parsers(){
+        assert_eq!(parse_os_release("NAME=\"Ubuntu\"\nPRETTY_NAME=\"Ubuntu 24.04 LTS\"\n").as_deref(), Some("Ubuntu 24.04 LTS"));
+        assert_eq!(parse_mem_available("MemTotal:  2048 kB\nMemAvailable:    1024 kB\n"), Some(1_048_576));
+        assert_eq!(parse_mem_available("MemTotal:  2048 kB\n"), None);
+        assert_eq!(detect_container(true, false, false, "0::/"), Some("docker"));
+        assert_eq!(detect_container(false, false, false, "0::/kubepods/besteffort/pod1"), Some("kubernetes"));
+        assert_eq!(detect_container(false, true, false, ""), Some("podman"));
+        assert_eq!(detect_container(false, false, false, "0::/init.scope"), None);
+        assert_eq!(parse_boot_time("cpu  1 2 3\nbtime 1760832000\nprocesses 10\n"), Some(1_760_832_000));
+        let stat = "4242 (my (app) svc) S 1 4242 4242 0 -1 4194560 100 0 0 0 5 2 0 0 20 0 4 0 123456 1000 50";
+        assert_eq!(parse_start_ticks(stat), Some(123_456));
+        assert_eq!(parse_start_ticks("4242 (svc) S 1"), None);
+    }
+}
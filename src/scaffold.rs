@@ -0,0 +1,182 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bt_any_error::any_err::AnyErr;
+use bt_logger::log_info;
+use yaml_rust2::Yaml;
+
+use crate::app_config::{DEFAULT_API_PATH, DEFAULT_APP_PATH, DEFAULT_FILES_APP_DIR};
+use crate::config_value::parse_yaml;
+use crate::migration::{CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION};
+use crate::schema::ENVIRONMENTS_KEY;
+use crate::server_config::{DEFAULT_HOST, DEFAULT_PORT};
+
+pub const APP_CONFIG_FILE_NAME: &str = "app-config.yml";
+pub const SRV_CONFIG_FILE_NAME: &str = "server-config.yml";
+
+///Commented starter app-config file with one block per environment. Values are the defaults used when a key is missing.
+pub fn starter_app_config(environments: &[&str]) -> String {
+    let mut yml = format!("# {}
+# Keys of an environment that are missing take the default shown here.
+{}: {}
+# Application name. Defaults to the Cargo package name.
+# app_name: My App
+# Environment used when none is given on the command line or in BT_ENV.
+# environment: {}
+# Application versions this file works with (inclusive).
+# min_app_version: 1.0.0
+# max_app_version: 1.9.0
+
+{}:
+", APP_CONFIG_FILE_NAME, CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION, environments.first().unwrap_or(&"dev"), ENVIRONMENTS_KEY);
+    for env in environments {
+        yml.push_str(&format!("  {}:
+    # Application name in this environment. Overrides the top level app_name.
+    # app_name: My App ({})
+    # Folder with the files of the web application
+    files_app_dir: {}
+    # Fail when the folder has no index.html (strict validation)
+    # require_index_html: true
+    # Base URL path of the web application
+    app_path: {}
+    # Base URL path of the API
+    api_path: {}
+    # Remote agent
+    # agent:
+    #   host: localhost
+    #   port: 8080
+    #   secure: true
+    #   end_point: /chat
+    # API end points. The path defaults to /<id>
+    # end_points:
+    #   - id: chat
+    #     path: /chat
+", env, env, DEFAULT_FILES_APP_DIR, DEFAULT_APP_PATH, DEFAULT_API_PATH));
+    }
+    yml
+}
+
+///Commented starter server-config file with one block per environment. Values are the defaults used when a key is missing.
+pub fn starter_server_config(environments: &[&str]) -> String {
+    let mut yml = format!("# {}
+# Keys of an environment that are missing take the default shown here.
+{}: {}
+# Environment used when none is given on the command line or in BT_ENV.
+# environment: {}
+
+{}:
+", SRV_CONFIG_FILE_NAME, CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION, environments.first().unwrap_or(&"dev"), ENVIRONMENTS_KEY);
+    for env in environments {
+        yml.push_str(&format!("  {}:
+    server:
+      # Listening address
+      host: {}
+      port: {}
+      # Serve over TLS
+      secure: true
+", env, DEFAULT_HOST, DEFAULT_PORT));
+    }
+    yml
+}
+
+///Environment names are written as plain YAML keys, so they may only use `[A-Za-z0-9_.-]` and must read back
+/// as strings (not `true`, `null`, `1` or `-`).
+fn is_valid_environment_name(env: &str) -> bool {
+    !env.is_empty()
+        && env.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        && matches!(parse_yaml(env), Ok(Yaml::String(key)) if key == env)
+}
+
+///Writes starter `app-config.yml` and `server-config.yml` files into `dir` (created if needed) for `environments`.
+/// Existing files are not overwritten unless `force` is true. Returns the paths written.
+pub fn write_starter_configs(dir: &Path, environments: &[&str], force: bool) -> Result<Vec<PathBuf>, AnyErr> {
+    if environments.is_empty() {
+        return Err("At least one environment is required.".into());
+    }
+    for (idx, env) in environments.iter().enumerate() {
+        if !is_valid_environment_name(env) {
+            return Err(format!("Invalid environment name '{}'. Use letters, digits, '_', '.' or '-'.", env).into());
+        }
+        if *env == ENVIRONMENTS_KEY || environments[..idx].contains(env) {
+            return Err(format!("Environment '{}' is reserved or duplicated.", env).into());
+        }
+    }
+
+    let files = [
+        (dir.join(APP_CONFIG_FILE_NAME), starter_app_config(environments)),
+        (dir.join(SRV_CONFIG_FILE_NAME), starter_server_config(environments)),
+    ];
+    //Check both files first so nothing is written when one of them already exists
+    if !force && let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+        return Err(format!("'{}' already exists. Use force to overwrite it.", path.display()).into());
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Cannot create '{}': {}", dir.display(), e))?;
+    let mut written = Vec::new();
+    for (path, content) in files {
+        fs::write(&path, content).map_err(|e| format!("Cannot write '{}': {}", path.display(), e))?;
+        log_info!("", "Created '{}'.", path.display());
+        written.push(path);
+    }
+    Ok(written)
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod scaffold_tests {
+    use bt_logger::{build_logger, LogLevel, LogTarget};
+
+    use crate::app_config::AppConfig;
+    use crate::app_info::{AppInfo, CargoPkg};
+    use crate::schema::ValidationMode;
+    use crate::server_config::ServerConfig;
+
+    use super::{is_valid_environment_name, write_starter_configs};
+
+    #[test]
+    pub fn test_write_starter_configs(){
+        build_logger("BACHUETECH","SCAFFOLD",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let dir = std::env::temp_dir().join(format!("bt_core_config_scaffold_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let written = write_starter_configs(&dir, &["dev", "prod"], false).unwrap();
+        assert_eq!(written.len(), 2);
+
+        let app_yml = std::fs::read_to_string(&written[0]).unwrap();
+        let srv_yml = std::fs::read_to_string(&written[1]).unwrap();
+        let app_info = AppInfo::new(CargoPkg::default(), "Scaffold", "1.0.0", "", "");
+        for env in ["dev", "prod"] {
+            let ac = AppConfig::builder().environment(env).build().unwrap();
+            let loaded = AppConfig::new_with_validation(env, &app_info, Some(&app_yml), ValidationMode::Strict).unwrap();
+            assert_eq!(loaded.get_environment(), env);
+            assert_eq!(loaded.get_app_path(), ac.get_app_path());
+            assert_eq!(loaded.get_api_path(), ac.get_api_path());
+            assert_eq!(loaded.get_file_app_dir(), ac.get_file_app_dir());
+            let sc = ServerConfig::new_with_validation(env, Some(&srv_yml), ValidationMode::Strict).unwrap();
+            assert_eq!(sc.get_tcp_listener(), "localhost:23339");
+        }
+
+        let err = write_starter_configs(&dir, &["dev"], false).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert!(write_starter_configs(&dir, &["dev"], true).is_ok());
+        assert!(write_starter_configs(&dir, &["dev", "dev"], true).is_err());
+        assert!(write_starter_configs(&dir, &[], true).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    pub fn test_environment_names(){
+        for env in ["dev", "jeremy_dev", "prod-eu.1", "Dev2"] {
+            assert!(is_valid_environment_name(env), "{}", env);
+        }
+        for env in ["", "[x]", "*a", "&b", "true", "null", "1", "1.0", "-", "a b", "a:b", "#x", "~"] {
+            assert!(!is_valid_environment_name(env), "{}", env);
+        }
+        let dir = std::env::temp_dir().join(format!("bt_core_config_scaffold_names_{}", std::process::id()));
+        let err = write_starter_configs(&dir, &["dev", "true"], false).unwrap_err();
+        assert!(err.to_string().contains("Invalid environment name 'true'"));
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,539 @@
+use std::fmt;
+
+use bt_logger::log_warning;
+use serde_json::{json, Map, Value};
+use yaml_rust2::yaml::Hash;
+use yaml_rust2::Yaml;
+
+use crate::config_value::{yaml_key_to_string, yaml_scalar_to_string, yaml_type_name};
+use crate::migration::{CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION};
+use crate::version::Version;
+
+///Type and constraints of a configuration value.
+#[derive(Debug)]
+pub enum ValueKind {
+    String,
+    ///URL path. Must start with '/'.
+    UrlPath,
+    ///File system path. Must not be empty.
+    FilePath,
+    Integer{ min: i64, max: i64 },
+    Boolean,
+    ///Semantic version (e.g. `1.2.0` or `v1.2`). YAML numbers such as `1.2` are accepted.
+    Version,
+    Map(&'static [Field]),
+    List(&'static ValueKind),
+}
+
+///A known configuration key.
+#[derive(Debug)]
+pub struct Field {
+    pub name: &'static str,
+    pub kind: ValueKind,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+///Top level key holding the environment blocks. Files without it keep their environments at the top level.
+pub const ENVIRONMENTS_KEY: &str = "environments";
+
+///An environment defined in a configuration file
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvironmentInfo {
+    pub name: String,
+    ///True when the block has no keys, so every value is a default
+    pub is_empty: bool,
+}
+
+///Declared layout of a configuration file: top level metadata keys plus the keys allowed in each environment block.
+#[derive(Debug)]
+pub struct ConfigSchema {
+    pub title: &'static str,
+    pub metadata: &'static [Field],
+    pub environment: &'static [Field],
+}
+
+///Invalid values are errors. Unknown keys are warnings, unless validating in strict mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+///How warnings are handled when loading a configuration file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationMode {
+    ///Warnings are logged and loading continues.
+    #[default]
+    Lenient,
+    ///Warnings are reported as errors.
+    Strict,
+}
+
+///A single schema violation. `key_path` is the dotted path from the root of the file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaViolation {
+    pub key_path: String,
+    pub message: String,
+    pub severity: Severity,
+}
+
+impl SchemaViolation {
+    fn error(key_path: &str, message: String) -> Self {
+        Self{ key_path: key_path.to_owned(), message, severity: Severity::Error }
+    }
+
+    fn warning(key_path: &str, message: String) -> Self {
+        Self{ key_path: key_path.to_owned(), message, severity: Severity::Warning }
+    }
+}
+
+///All the schema violations found while loading a configuration file.
+#[derive(Debug)]
+pub struct SchemaErrors {
+    pub title: &'static str,
+    pub environment: String,
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key_path, self.message)
+    }
+}
+
+impl fmt::Display for SchemaErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid {} for environment '{}' ({} violation(s)):", self.title, self.environment, self.violations.len())?;
+        for v in &self.violations {
+            write!(f, "\n  - {}", v)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SchemaErrors {}
+
+const CONFIG_VERSION: ValueKind = ValueKind::Integer{ min: 1, max: CURRENT_CONFIG_VERSION };
+const PORT: ValueKind = ValueKind::Integer{ min: 0, max: 65535 };
+
+const AGENT_FIELDS: &[Field] = &[
+    Field{ name: "host", kind: ValueKind::String, required: false, description: "Host of the remote AI agent." },
+    Field{ name: "port", kind: PORT, required: false, description: "Port of the remote AI agent." },
+    Field{ name: "secure", kind: ValueKind::Boolean, required: false, description: "Use https (true) or http (false)." },
+    Field{ name: "end_point", kind: ValueKind::UrlPath, required: false, description: "Path of the agent end point." },
+];
+
+const END_POINT_FIELDS: &[Field] = &[
+    Field{ name: "id", kind: ValueKind::String, required: true, description: "End point identifier." },
+    Field{ name: "path", kind: ValueKind::UrlPath, required: false, description: "End point path. Defaults to '/<id>'." },
+];
+
+const APP_METADATA_FIELDS: &[Field] = &[
+    Field{ name: CONFIG_VERSION_KEY, kind: CONFIG_VERSION, required: false, description: "Layout version of the file. Defaults to 1." },
+    Field{ name: "app_name", kind: ValueKind::String, required: false, description: "Application display name." },
+    Field{ name: "version", kind: ValueKind::Version, required: false, description: "Version of the configuration file." },
+    Field{ name: "environment", kind: ValueKind::String, required: false, description: "Default running environment." },
+    Field{ name: "min_app_version", kind: ValueKind::Version, required: false, description: "Lowest application version compatible with the file." },
+    Field{ name: "max_app_version", kind: ValueKind::Version, required: false, description: "Highest application version compatible with the file." },
+];
+
+const APP_ENVIRONMENT_FIELDS: &[Field] = &[
+    Field{ name: "app_name", kind: ValueKind::String, required: false, description: "Application name in this environment. Overrides the top level app_name." },
+    Field{ name: "agent", kind: ValueKind::Map(AGENT_FIELDS), required: false, description: "Location of the remote AI agent." },
+    Field{ name: "files_app_dir", kind: ValueKind::FilePath, required: false, description: "Static files directory. Defaults to 'site'." },
+    Field{ name: "require_index_html", kind: ValueKind::Boolean, required: false, description: "Require an index.html in the static files directory. Defaults to false." },
+    Field{ name: "app_path", kind: ValueKind::UrlPath, required: false, description: "Application base URL. Defaults to '/app'." },
+    Field{ name: "api_path", kind: ValueKind::UrlPath, required: false, description: "API base URL. Defaults to '/api'." },
+    Field{ name: "end_points", kind: ValueKind::List(&ValueKind::Map(END_POINT_FIELDS)), required: false, description: "Application end points." },
+];
+
+const SERVER_FIELDS: &[Field] = &[
+    Field{ name: "host", kind: ValueKind::String, required: false, description: "Host or IP to bind. Defaults to 'localhost'." },
+    Field{ name: "port", kind: PORT, required: false, description: "Port to bind. Defaults to 23339." },
+    Field{ name: "secure", kind: ValueKind::Boolean, required: false, description: "Serve over TLS. Defaults to true." },
+];
+
+const SRV_METADATA_FIELDS: &[Field] = &[
+    Field{ name: CONFIG_VERSION_KEY, kind: CONFIG_VERSION, required: false, description: "Layout version of the file. Defaults to 1." },
+    Field{ name: "environment", kind: ValueKind::String, required: false, description: "Default running environment." },
+];
+
+const SRV_ENVIRONMENT_FIELDS: &[Field] = &[
+    Field{ name: "server", kind: ValueKind::Map(SERVER_FIELDS), required: false, description: "Server listener." },
+];
+
+///Schema of `config/core/app-config.yml`
+pub const APP_CONFIG_SCHEMA: ConfigSchema = ConfigSchema{ title: "app-config", metadata: APP_METADATA_FIELDS, environment: APP_ENVIRONMENT_FIELDS };
+
+///Schema of `config/core/server-config.yml`
+pub const SRV_CONFIG_SCHEMA: ConfigSchema = ConfigSchema{ title: "server-config", metadata: SRV_METADATA_FIELDS, environment: SRV_ENVIRONMENT_FIELDS };
+
+///How keys that are not declared in the schema are reported
+#[derive(Clone, Copy, PartialEq)]
+enum UnknownKeys {
+    ///Every unknown key is reported.
+    All,
+    ///Only keys close to a known key are reported. Used where custom keys are allowed (e.g. custom sections in an environment block).
+    TyposOnly,
+}
+
+impl ConfigSchema {
+    ///Validates the top level metadata and the block of `environment`. Returns every violation found.
+    /// Top level scalar keys and environment keys that look like a misspelled known key are reported as warnings.
+    pub fn validate(&self, config: &Yaml, environment: &str) -> Vec<SchemaViolation> {
+        let mut violations = Vec::new();
+        if !config.is_null() && config.as_hash().is_none() {
+            violations.push(SchemaViolation::error(".", format!("expected map, found {}", yaml_type_name(config))));
+            return violations;
+        }
+        validate_fields(self.metadata, config, "", &mut violations, None);
+        //Environment blocks are maps (or empty), so a top level scalar must be metadata.
+        if let Some(hash) = config.as_hash() {
+            for (key, value) in hash {
+                let key = yaml_key_to_string(key);
+                if !matches!(value, Yaml::Hash(_) | Yaml::Null) && !self.metadata.iter().any(|f| f.name == key) {
+                    violations.push(unknown_key_warning(&key, &key, self.metadata));
+                }
+            }
+        }
+        let environments = &config[ENVIRONMENTS_KEY];
+        if !matches!(environments, Yaml::Hash(_) | Yaml::BadValue) {
+            violations.push(SchemaViolation::error(ENVIRONMENTS_KEY, format!("expected map, found {}", yaml_type_name(environments))));
+        }
+
+        let env_path = if environments.as_hash().is_some() { join_path(ENVIRONMENTS_KEY, environment) } else { environment.to_owned() };
+        let env_block = environment_block(config, environment);
+        match env_block {
+            Yaml::Null | Yaml::BadValue => (),
+            Yaml::Hash(_) => validate_fields(self.environment, env_block, &env_path, &mut violations, Some(UnknownKeys::TyposOnly)),
+            other => violations.push(SchemaViolation::error(&env_path, format!("expected map, found {}", yaml_type_name(other)))),
+        }
+        violations
+    }
+
+    ///Names of the environment blocks in a configuration file.
+    /// If the file has an `environments:` map, its keys are the environments. Otherwise (legacy layout),
+    /// top level keys that are maps or empty and are not metadata.
+    pub fn environments(&self, config: &Yaml) -> Vec<String> {
+        if let Some(environments) = config[ENVIRONMENTS_KEY].as_hash() {
+            return environments.keys().map(yaml_key_to_string).collect();
+        }
+        config.as_hash().map(|hash| hash.iter()
+                .filter(|(_, value)| matches!(value, Yaml::Hash(_) | Yaml::Null))
+                .map(|(key, _)| yaml_key_to_string(key))
+                .filter(|key| key != ENVIRONMENTS_KEY && !self.metadata.iter().any(|f| f.name == key))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    ///Environments of a configuration file and whether they are empty
+    pub fn list_environments(&self, config: &Yaml) -> Vec<EnvironmentInfo> {
+        self.environments(config).into_iter()
+            .map(|name| {
+                let block = environment_block(config, &name);
+                let is_empty = block.is_null() || block.as_hash().is_some_and(|h| h.is_empty());
+                EnvironmentInfo{ name, is_empty }
+            })
+            .collect()
+    }
+
+    ///Same configuration with the environment blocks moved under an `environments:` map (legacy layout converted).
+    pub(crate) fn with_environments_key(&self, config: &Yaml) -> Yaml {
+        let Some(hash) = config.as_hash() else { return config.clone() };
+        if config[ENVIRONMENTS_KEY].as_hash().is_some() {
+            return config.clone();
+        }
+        let environments = self.environments(config);
+        let mut root = Hash::new();
+        let mut env_blocks = Hash::new();
+        for (key, value) in hash {
+            if environments.contains(&yaml_key_to_string(key)) {
+                env_blocks.insert(key.clone(), value.clone());
+            } else {
+                root.insert(key.clone(), value.clone());
+            }
+        }
+        root.insert(Yaml::String(ENVIRONMENTS_KEY.to_owned()), Yaml::Hash(env_blocks));
+        Yaml::Hash(root)
+    }
+
+    ///True if `key` is declared in the environment block
+    pub(crate) fn is_environment_field(&self, key: &str) -> bool {
+        self.environment.iter().any(|f| f.name == key)
+    }
+
+    ///Validates and splits the violations by `mode`. Returns the warnings, or an error with every violation that must stop loading.
+    pub fn check(&self, config: &Yaml, environment: &str, mode: ValidationMode) -> Result<Vec<SchemaViolation>, SchemaErrors> {
+        let (errors, warnings): (Vec<SchemaViolation>, Vec<SchemaViolation>) = self.validate(config, environment)
+            .into_iter()
+            .partition(|v| v.severity == Severity::Error || mode == ValidationMode::Strict);
+        if errors.is_empty() {
+            Ok(warnings)
+        } else {
+            Err(SchemaErrors{ title: self.title, environment: environment.to_owned(), violations: errors })
+        }
+    }
+
+    ///Like `check` but logs the warnings.
+    pub(crate) fn enforce(&self, config: &Yaml, environment: &str, mode: ValidationMode) -> Result<(), SchemaErrors> {
+        for warning in self.check(config, environment, mode)? {
+            log_warning!("","{} '{}': {}",self.title, warning.key_path, warning.message);
+        }
+        Ok(())
+    }
+
+    ///Exports the schema as a JSON Schema (draft-07) document. Every key that is not metadata is an environment block.
+    pub fn to_json_schema(&self) -> Value {
+        let mut root = fields_to_json_schema(self.metadata);
+        root["$schema"] = json!("http://json-schema.org/draft-07/schema#");
+        root["title"] = json!(self.title);
+        root["additionalProperties"] = json!({ "$ref": "#/definitions/environment" });
+        root["properties"][ENVIRONMENTS_KEY] = json!({
+            "type": "object",
+            "description": "Environment blocks. When present, top level environment blocks are ignored.",
+            "additionalProperties": { "$ref": "#/definitions/environment" },
+        });
+
+        let mut environment = fields_to_json_schema(self.environment);
+        environment["type"] = json!(["object", "null"]);
+        root["definitions"] = json!({ "environment": environment });
+        root
+    }
+}
+
+///Block of `environment`, from the `environments:` map when the file has one. BadValue if the environment is not defined.
+pub fn environment_block<'a>(config: &'a Yaml, environment: &str) -> &'a Yaml {
+    if config[ENVIRONMENTS_KEY].as_hash().is_some() {
+        &config[ENVIRONMENTS_KEY][environment]
+    } else if environment == ENVIRONMENTS_KEY {
+        &Yaml::BadValue
+    } else {
+        &config[environment]
+    }
+}
+
+fn join_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() { key.to_owned() } else { format!("{}.{}", parent, key) }
+}
+
+fn validate_fields(fields: &[Field], block: &Yaml, path: &str, violations: &mut Vec<SchemaViolation>, unknown_keys: Option<UnknownKeys>) {
+    for field in fields {
+        let key_path = join_path(path, field.name);
+        match &block[field.name] {
+            Yaml::BadValue | Yaml::Null => if field.required {
+                violations.push(SchemaViolation::error(&key_path, "required key is missing".to_owned()));
+            },
+            value => validate_value(&field.kind, value, &key_path, violations),
+        }
+    }
+
+    if let (Some(unknown_keys), Some(hash)) = (unknown_keys, block.as_hash()) {
+        for key in hash.keys().map(yaml_key_to_string) {
+            if fields.iter().any(|f| f.name == key) {
+                continue;
+            }
+            if unknown_keys == UnknownKeys::All || suggest_key(&key, fields).is_some() {
+                violations.push(unknown_key_warning(&join_path(path, &key), &key, fields));
+            }
+        }
+    }
+}
+
+fn unknown_key_warning(key_path: &str, key: &str, fields: &[Field]) -> SchemaViolation {
+    match suggest_key(key, fields) {
+        Some(known) => SchemaViolation::warning(key_path, format!("unknown key '{}', did you mean '{}'?", key, known)),
+        None => SchemaViolation::warning(key_path, format!("unknown key '{}'", key)),
+    }
+}
+
+///Closest known key within a small edit distance, if any
+fn suggest_key(key: &str, fields: &[Field]) -> Option<&'static str> {
+    fields.iter()
+        .map(|f| (edit_distance(key, f.name), f.name))
+        .filter(|(d, name)| *d <= 2 && *d < name.len())
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, name)| name)
+}
+
+///Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+fn validate_value(kind: &ValueKind, value: &Yaml, key_path: &str, violations: &mut Vec<SchemaViolation>) {
+    let mut violation = |message: String| violations.push(SchemaViolation::error(key_path, message));
+    match kind {
+        ValueKind::String => if value.as_str().is_none() {
+            violation(format!("expected string, found {}", yaml_type_name(value)));
+        },
+        ValueKind::UrlPath => match value.as_str() {
+            Some(p) if p.starts_with('/') => (),
+            Some(p) => violation(format!("expected a path starting with '/', found '{}'", p)),
+            None => violation(format!("expected string, found {}", yaml_type_name(value))),
+        },
+        ValueKind::FilePath => match value.as_str() {
+            Some(p) if !p.trim().is_empty() && !p.contains('\0') => (),
+            Some(p) => violation(format!("expected a file path, found '{}'", p)),
+            None => violation(format!("expected string, found {}", yaml_type_name(value))),
+        },
+        ValueKind::Integer{ min, max } => match value.as_i64() {
+            Some(i) if (*min..=*max).contains(&i) => (),
+            Some(i) => violation(format!("value {} out of range [{}, {}]", i, min, max)),
+            None => violation(format!("expected integer, found {}", yaml_type_name(value))),
+        },
+        ValueKind::Boolean => if value.as_bool().is_none() {
+            violation(format!("expected boolean, found {}", yaml_type_name(value)));
+        },
+        ValueKind::Version => match yaml_scalar_to_string(value).filter(|_| value.as_bool().is_none()) {
+            Some(v) => if let Err(e) = v.parse::<Version>() {
+                violation(e);
+            },
+            None => violation(format!("expected version, found {}", yaml_type_name(value))),
+        },
+        ValueKind::Map(fields) => if value.as_hash().is_some() {
+            validate_fields(fields, value, key_path, violations, Some(UnknownKeys::All));
+        } else {
+            violation(format!("expected map, found {}", yaml_type_name(value)));
+        },
+        ValueKind::List(item_kind) => match value.as_vec() {
+            Some(items) => for (idx, item) in items.iter().enumerate() {
+                validate_value(item_kind, item, &join_path(key_path, &idx.to_string()), violations);
+            },
+            None => violation(format!("expected list, found {}", yaml_type_name(value))),
+        },
+    }
+}
+
+fn fields_to_json_schema(fields: &[Field]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in fields {
+        let mut property = kind_to_json_schema(&field.kind);
+        property["description"] = json!(field.description);
+        properties.insert(field.name.to_owned(), property);
+        if field.required {
+            required.push(json!(field.name));
+        }
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    schema
+}
+
+fn kind_to_json_schema(kind: &ValueKind) -> Value {
+    match kind {
+        ValueKind::String => json!({ "type": "string" }),
+        ValueKind::UrlPath => json!({ "type": "string", "pattern": "^/" }),
+        ValueKind::FilePath => json!({ "type": "string", "minLength": 1 }),
+        ValueKind::Integer{ min, max } => json!({ "type": "integer", "minimum": min, "maximum": max }),
+        ValueKind::Boolean => json!({ "type": "boolean" }),
+        ValueKind::Version => json!({ "type": ["string", "number"], "pattern": "^[vV]?[0-9]+(\\.[0-9]+){0,2}(-[0-9A-Za-z.-]+)?(\\+[0-9A-Za-z.-]+)?$" }),
+        ValueKind::Map(fields) => {
+            let mut schema = fields_to_json_schema(fields);
+            schema["additionalProperties"] = json!(false);
+            schema
+        },
+        ValueKind::List(item_kind) => json!({ "type": "array", "items": kind_to_json_schema(item_kind) }),
+    }
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod schema_tests {
+    use yaml_rust2::YamlLoader;
+
+    use super::{edit_distance, EnvironmentInfo, Severity, ValidationMode, APP_CONFIG_SCHEMA, SRV_CONFIG_SCHEMA};
+
+    #[test]
+    pub fn test_validate_success(){
+        const YML_CONTENT: &str = include_str!("../config/core/app-config.yml");
+        let yml = &YamlLoader::load_from_str(YML_CONTENT).unwrap()[0];
+        for env in ["dev", "devNone", "empty", "jeremy_dev", "embed_dev"] {
+            assert_eq!(APP_CONFIG_SCHEMA.validate(yml, env), vec![]);
+        }
+    }
+
+    #[test]
+    pub fn test_validate_reports_all_violations(){
+        let yml = &YamlLoader::load_from_str("app_name: 1\ndev:\n  app_path: app\n  agent:\n    port: 70000\n    secure: yes\n  end_points:\n    - path: /chat\n").unwrap()[0];
+        let errors = APP_CONFIG_SCHEMA.check(yml, "dev", ValidationMode::Lenient).unwrap_err();
+        let paths: Vec<&str> = errors.violations.iter().map(|v| v.key_path.as_str()).collect();
+        assert_eq!(paths, vec!["app_name", "dev.agent.port", "dev.agent.secure", "dev.app_path", "dev.end_points.0.id"]);
+        assert!(errors.to_string().contains("5 violation(s)"));
+    }
+
+    #[test]
+    pub fn test_validate_server_port_bounds(){
+        let yml = &YamlLoader::load_from_str("prod:\n  server:\n    port: -1\n    host: [a]\n").unwrap()[0];
+        assert_eq!(SRV_CONFIG_SCHEMA.validate(yml, "prod").len(), 2);
+    }
+
+    #[test]
+    pub fn test_unknown_keys_suggestions(){
+        let yml = &YamlLoader::load_from_str("app_nme: X\ndev:\n  api_pth: /api\n  database:\n    url: x\n  agent:\n    hots: localhost\n    timeout: 5\n  end_points:\n    - id: chat\n      paht: /chat\n").unwrap()[0];
+        let violations = APP_CONFIG_SCHEMA.validate(yml, "dev");
+        let messages: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+        assert_eq!(messages, vec![
+            "app_nme: unknown key 'app_nme', did you mean 'app_name'?",
+            "dev.agent.hots: unknown key 'hots', did you mean 'host'?",
+            "dev.agent.timeout: unknown key 'timeout'",
+            "dev.end_points.0.paht: unknown key 'paht', did you mean 'path'?",
+            "dev.api_pth: unknown key 'api_pth', did you mean 'api_path'?",
+        ]);
+        assert!(violations.iter().all(|v| v.severity == Severity::Warning));
+        assert_eq!(APP_CONFIG_SCHEMA.check(yml, "dev", ValidationMode::Lenient).unwrap().len(), 5);
+        assert_eq!(APP_CONFIG_SCHEMA.check(yml, "dev", ValidationMode::Strict).unwrap_err().violations.len(), 5);
+    }
+
+    #[test]
+    pub fn test_environments(){
+        const YML_CONTENT: &str = include_str!("../config/core/app-config.yml");
+        let yml = &YamlLoader::load_from_str(YML_CONTENT).unwrap()[0];
+        assert_eq!(APP_CONFIG_SCHEMA.environments(yml), vec!["empty", "dev", "devNone", "jeremy_dev", "embed_dev"]);
+        assert_eq!(APP_CONFIG_SCHEMA.list_environments(yml)[0], EnvironmentInfo{ name: "empty".to_owned(), is_empty: true });
+    }
+
+    #[test]
+    pub fn test_environments_key(){
+        let yml = &YamlLoader::load_from_str("environment: dev\nenvironments:\n  dev:\n    server:\n      port: 99999\n  qa: {}\nlegacy:\n  server:\n    port: 1\n").unwrap()[0];
+        assert_eq!(SRV_CONFIG_SCHEMA.list_environments(yml), vec![
+            EnvironmentInfo{ name: "dev".to_owned(), is_empty: false },
+            EnvironmentInfo{ name: "qa".to_owned(), is_empty: true },
+        ]);
+        let violations = SRV_CONFIG_SCHEMA.validate(yml, "dev");
+        assert_eq!(violations[0].key_path, "environments.dev.server.port");
+        assert!(SRV_CONFIG_SCHEMA.to_json_schema()["properties"]["environments"].is_object());
+    }
+
+    #[test]
+    pub fn test_edit_distance(){
+        assert_eq!(edit_distance("end_point", "end_points"), 1);
+        assert_eq!(edit_distance("api_pth", "api_path"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    pub fn test_json_schema_export(){
+        let schema = APP_CONFIG_SCHEMA.to_json_schema();
+        assert_eq!(schema["title"], "app-config");
+        assert_eq!(schema["definitions"]["environment"]["properties"]["end_points"]["items"]["required"][0], "id");
+        let srv = SRV_CONFIG_SCHEMA.to_json_schema();
+        assert_eq!(srv["definitions"]["environment"]["properties"]["server"]["properties"]["port"]["maximum"], 65535);
+    }
+}
@@ -1,71 +1,170 @@
 
+use std::collections::BTreeMap;
+
 use bt_any_error::any_err::AnyErr;
-use bt_logger::{log_error, log_info, log_warning};
-use bt_yaml_utils::{get_yaml, get_yaml_from_string};
+use bt_logger::log_error;
+use bt_yaml_utils::get_yaml_from_string;
 use yaml_rust2::Yaml;
 
+use crate::config_file::{find_config_file, locate_config_file, read_config_file, ConfigLocation};
+use crate::config_value::{emit_yaml, read_layered_yaml, yaml_map, yaml_opt_str};
+use crate::migration::{upgrade_config, CONFIG_VERSION_KEY, CURRENT_CONFIG_VERSION};
+use crate::environment::{resolve_environment, EnvironmentCandidates, EnvironmentSource, ResolvedEnvironment, DEFAULT_RUN_ENV};
+use crate::schema::{environment_block, EnvironmentInfo, ValidationMode, ENVIRONMENTS_KEY, SRV_CONFIG_SCHEMA};
+
 const SRV_YML_CONFIG: &str = "config/core/server-config.yml";
 const SRV_YML_CONFIG_ENV_VAR_NAME: &str = "BT_SRV_CONFIGYMLFILE";
 
-const DEFAULT_PORT: i64  = 23339;
-const DEFAULT_HOST: &str = "localhost";
+pub(crate) const DEFAULT_PORT: i64  = 23339;
+pub(crate) const DEFAULT_HOST: &str = "localhost";
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ServerConfig {
+    environment: String,
+    environment_source: EnvironmentSource,
     host: String,
     port: u16,
     secure: bool,
+    location: Option<ConfigLocation>,
+}
+
+///Builds a `ServerConfig` without a YAML file. Values are validated with the same rules as the YAML loader.
+#[derive(Clone, Debug, Default)]
+pub struct ServerConfigBuilder {
+    environment: Option<String>,
+    host: Option<String>,
+    port: Option<i64>,
+    secure: Option<bool>,
 }
 
 impl ServerConfig {
     /// Constructor. Reading from YAML file
     /// Arguments:
     /// run_env: Receives the current running environment (The file may contain several environments)
-    /// embed_config: Content of the YML config file. None to use env variable or default.
+    /// embed_config: Content of the YML config file. None to use env variable or search paths.
     pub fn new(running_environment: &str, embed_config: Option<&str>) -> Result<Self, AnyErr> {
+        Self::new_with_validation(running_environment, embed_config, ValidationMode::Lenient)
+    }
 
-        let srv_config: Yaml = if let Some(yml_cfg) = embed_config {
-                                    get_yaml_from_string(yml_cfg)?
-                                } else {
-                                    get_yaml(SRV_YML_CONFIG_ENV_VAR_NAME, SRV_YML_CONFIG)?
-                                };
-
-        let svr_environment: &str;
-
-        if running_environment.trim().is_empty() || srv_config[running_environment].is_badvalue(){
-            log_error!("","Invalid Running Environment '{}'. Will use default to continue.",running_environment);  
-            #[cfg(debug_assertions)]
-                const RUN_ENV: &str = "dev";
-            #[cfg(not(debug_assertions))]
-                const RUN_ENV: &str = "prod";                     
-            svr_environment = srv_config["environment"].as_str().unwrap_or(RUN_ENV);
-            log_warning!("","Could not find Running Environment '{}'. Using current default '{}' to continue.",running_environment, svr_environment);                    
-        }else{
-            svr_environment = running_environment;
-            log_info!("","Using current environment '{}'.",&svr_environment);
+    ///Same as `new`. In `ValidationMode::Strict` unknown or misspelled keys are errors instead of logged warnings.
+    pub fn new_with_validation(running_environment: &str, embed_config: Option<&str>, validation: ValidationMode) -> Result<Self, AnyErr> {
+        Self::new_with_candidates(&EnvironmentCandidates::explicit(running_environment), embed_config, validation)
+    }
+
+    ///Same as `new_with_validation`, choosing the environment from `candidates`. Use `EnvironmentCandidates::from_process`
+    /// to also read the `--env`/`-e` command line flag and an env variable such as `BT_ENV`.
+    pub fn new_with_candidates(candidates: &EnvironmentCandidates, embed_config: Option<&str>, validation: ValidationMode) -> Result<Self, AnyErr> {
+        let (srv_config, location) = Self::read_yaml(embed_config)?;
+        let svr_environment = resolve_environment(&srv_config, candidates);
+        Ok(Self::from_yaml(&srv_config, &svr_environment, validation)?.with_location(location))
+    }
+
+    ///Layered configuration: `base_config` (usually embedded in the binary) with the on-disk file merged over it.
+    /// The file is taken from `BT_SRV_CONFIGYMLFILE` or the search paths and is optional unless the env variable is set.
+    pub fn new_layered(running_environment: &str, base_config: &str, validation: ValidationMode) -> Result<Self, AnyErr> {
+        let (srv_config, location) = Self::read_yaml_layered(base_config)?;
+        let svr_environment = resolve_environment(&srv_config, &EnvironmentCandidates::explicit(running_environment));
+        Ok(Self::from_yaml(&srv_config, &svr_environment, validation)?.with_location(location))
+    }
+
+    ///Builder to construct the configuration programmatically (e.g. in unit tests)
+    pub fn builder() -> ServerConfigBuilder {
+        ServerConfigBuilder::default()
+    }
+
+    ///Reads the server-config YAML and the file location (None for embedded content).
+    /// embed_config: Content of the YML config file. None to use env variable or search paths.
+    pub(crate) fn read_yaml(embed_config: Option<&str>) -> Result<(Yaml, Option<ConfigLocation>), AnyErr> {
+        match embed_config {
+            Some(yml_cfg) => Ok((get_yaml_from_string(yml_cfg)?, None)),
+            None => {
+                let location = locate_config_file(SRV_YML_CONFIG_ENV_VAR_NAME, SRV_YML_CONFIG)?;
+                Ok((read_config_file(&location.path)?, Some(location)))
+            },
         }
+    }
 
-        let mut srv_port = srv_config[svr_environment]["server"]["port"]
+    ///Reads `base_config` with the server-config file (env variable or search paths), if present, merged over it.
+    pub(crate) fn read_yaml_layered(base_config: &str) -> Result<(Yaml, Option<ConfigLocation>), AnyErr> {
+        let location = find_config_file(SRV_YML_CONFIG_ENV_VAR_NAME, SRV_YML_CONFIG)?;
+        Ok((read_layered_yaml(&SRV_CONFIG_SCHEMA, base_config, location.as_ref().map(|l| l.path.as_path()))?, location))
+    }
+
+    ///Builds the configuration of `environment`, which is expected to be already resolved.
+    pub(crate) fn from_yaml(srv_config: &Yaml, environment: &ResolvedEnvironment, validation: ValidationMode) -> Result<Self, AnyErr> {
+        let srv_config = &upgrade_config(&SRV_CONFIG_SCHEMA, srv_config.clone())?;
+        let svr_environment = environment.name.as_str();
+        if let Err(e) = SRV_CONFIG_SCHEMA.enforce(srv_config, svr_environment, validation) {
+            log_error!("","{}",e);
+            return Err(e.into());
+        }
+
+        let env_block = environment_block(srv_config, svr_environment);
+        let srv_port = env_block["server"]["port"]
             .as_i64()
             .unwrap_or(DEFAULT_PORT);
-        srv_port = if !(0..=65535).contains(&srv_port) {
-            DEFAULT_PORT
-        } else {
-            srv_port
-        };
+        debug_assert!((0..=65535).contains(&srv_port), "the schema rejects ports out of range");
 
         Ok(Self {
-            host: srv_config[svr_environment]["server"]["host"]
+            environment: svr_environment.to_owned(),
+            environment_source: environment.source.clone(),
+            host: env_block["server"]["host"]
                 .as_str()
                 .unwrap_or(DEFAULT_HOST)
                 .to_string(),
             port: srv_port as u16,
-            secure: srv_config[svr_environment]["server"]["secure"]
+            secure: env_block["server"]["secure"]
                 .as_bool()
                 .unwrap_or(true),
+            location: None,
         })
     }
 
+    ///Environments defined in the server-config file and whether they are empty.
+    /// embed_config: Content of the YML config file. None to use env variable or search paths.
+    pub fn list_environments(embed_config: Option<&str>) -> Result<Vec<EnvironmentInfo>, AnyErr> {
+        Ok(SRV_CONFIG_SCHEMA.list_environments(&Self::read_yaml(embed_config)?.0))
+    }
+
+    ///JSON Schema (draft-07) of the server-config YAML file. Useful for editor completion and validation.
+    pub fn json_schema() -> String {
+        serde_json::to_string_pretty(&SRV_CONFIG_SCHEMA.to_json_schema()).unwrap_or_default()
+    }
+
+    ///File the configuration was read from and the search location that found it. None for embedded or built configurations.
+    pub fn get_config_location(&self) -> Option<&ConfigLocation> {
+        self.location.as_ref()
+    }
+
+    pub(crate) fn with_location(mut self, location: Option<ConfigLocation>) -> Self {
+        self.location = location;
+        self
+    }
+
+    ///Effective values flattened by dotted key path (e.g. `server.port`). Includes defaults.
+    pub fn effective_values(&self) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("server.host".to_owned(), self.host.clone()),
+            ("server.port".to_owned(), self.port.to_string()),
+            ("server.secure".to_owned(), self.secure.to_string()),
+        ])
+    }
+
+    ///Fully resolved configuration in the layout of `config/core/server-config.yml`, with this environment only.
+    /// Loading the result again gives the same configuration.
+    pub fn to_yaml(&self) -> Result<String, AnyErr> {
+        let server = yaml_map(vec![
+            ("host", Yaml::String(self.host.clone())),
+            ("port", Yaml::Integer(self.port.into())),
+            ("secure", Yaml::Boolean(self.secure)),
+        ]);
+        emit_yaml(&yaml_map(vec![
+            (CONFIG_VERSION_KEY, Yaml::Integer(CURRENT_CONFIG_VERSION)),
+            ("environment", Yaml::String(self.environment.clone())),
+            (ENVIRONMENTS_KEY, yaml_map(vec![(self.environment.as_str(), yaml_map(vec![("server", server)]))])),
+        ]))
+    }
+
     pub fn get_tcp_listener(&self) -> String {
         format!("{}:{}", self.host.clone(), self.port)
     }
@@ -80,7 +179,59 @@ impl ServerConfig {
 
     pub fn get_host(&self) -> String {
         self.host.clone()
+    }
+
+    pub fn get_environment(&self) -> String {
+        self.environment.clone()
     }    
+
+    ///Rule that chose the running environment. Useful for logging.
+    pub fn get_environment_source(&self) -> &EnvironmentSource {
+        &self.environment_source
+    }
+}
+
+impl ServerConfigBuilder {
+    ///Environment name. Defaults to `dev` in debug builds and `prod` in release builds.
+    pub fn environment(mut self, environment: &str) -> Self {
+        self.environment = Some(environment.to_owned());
+        self
+    }
+
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = Some(host.to_owned());
+        self
+    }
+
+    pub fn port(mut self, port: i64) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    ///Serve over TLS
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = Some(secure);
+        self
+    }
+
+    ///Validates and builds the configuration
+    pub fn build(self) -> Result<ServerConfig, AnyErr> {
+        let resolved = match &self.environment {
+            Some(environment) => ResolvedEnvironment::explicit(environment),
+            None => ResolvedEnvironment{ name: DEFAULT_RUN_ENV.to_owned(), source: EnvironmentSource::BuildDefault },
+        };
+        let environment = resolved.name.clone();
+        let server = yaml_map(vec![
+            ("host", yaml_opt_str(&self.host)),
+            ("port", self.port.map(Yaml::Integer).unwrap_or(Yaml::BadValue)),
+            ("secure", self.secure.map(Yaml::Boolean).unwrap_or(Yaml::BadValue)),
+        ]);
+        let srv_config = yaml_map(vec![
+            (CONFIG_VERSION_KEY, Yaml::Integer(CURRENT_CONFIG_VERSION)),
+            (ENVIRONMENTS_KEY, yaml_map(vec![(environment.as_str(), yaml_map(vec![("server", server)]))])),
+        ]);
+        ServerConfig::from_yaml(&srv_config, &resolved, ValidationMode::Lenient)
+    }
 }
 
 pub fn get_srv_config(current_env: &str,  embed_config: Option<&str>) -> Result<ServerConfig, AnyErr> {
@@ -91,9 +242,12 @@ pub fn get_srv_config(current_env: &str,  embed_config: Option<&str>) -> Result<
 // UNIT TEST 
 //***********/
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod server_config_tests {
     use bt_logger::{build_logger, LogLevel, LogTarget};
 
+    use crate::environment::EnvironmentSource;
+    use crate::schema::ValidationMode;
     use crate::server_config::get_srv_config;
 
     use super::ServerConfig;
@@ -132,6 +286,14 @@ mod server_config_tests {
         assert_eq!(sc.is_secure(),false);
     }
 
+    #[test]
+    pub fn test_svr_conf_config_location(){
+        build_logger("BACHUETECH","SERVER_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let sc = ServerConfig::new("dev", None).unwrap();
+        assert!(sc.get_config_location().unwrap().path.ends_with("config/core/server-config.yml"));
+        assert!(ServerConfig::new("dev", Some("environments:\n  dev:\n")).unwrap().get_config_location().is_none());
+    }
+
     #[test]
     pub fn test_svr_conf_tpc_listener_dev_from_str(){
         build_logger("BACHUETECH","SERVER_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
@@ -154,4 +316,68 @@ mod server_config_tests {
         assert_eq!(sc.get_tcp_listener(),res);
         assert_eq!(sc.is_secure(),true);
     }
+
+    #[test]
+    pub fn test_svr_conf_list_environments(){
+        let envs = ServerConfig::list_environments(None).unwrap();
+        let names: Vec<&str> = envs.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["dev", "empty", "prod"]);
+        assert!(envs[1].is_empty);
+        const LEGACY_CONTENT: &str = "dev:\n  server:\n    port: 8080\n";
+        assert_eq!(ServerConfig::new("dev", Some(LEGACY_CONTENT)).unwrap().get_port(),8080);
+        assert_eq!(ServerConfig::list_environments(Some(LEGACY_CONTENT)).unwrap().len(),1);
+    }
+
+    #[test]
+    pub fn test_svr_conf_builder(){
+        build_logger("BACHUETECH","SERVER_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let sc = ServerConfig::builder().environment("unit").host("0.0.0.0").port(8443).secure(true).build().unwrap();
+        assert_eq!(sc.get_tcp_listener(),"0.0.0.0:8443");
+        assert_eq!(sc.get_environment(),"unit");
+        assert_eq!(sc.get_environment_source(),&EnvironmentSource::Explicit);
+        assert!(sc.is_secure());
+        assert_eq!(ServerConfig::builder().build().unwrap().get_tcp_listener(),"localhost:23339");
+        let err = ServerConfig::builder().port(-1).build().unwrap_err();
+        assert!(err.to_string().contains("server.port: value -1 out of range"));
+    }
+
+    #[test]
+    pub fn test_svr_conf_to_yaml_round_trip(){
+        build_logger("BACHUETECH","SERVER_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        let sc = ServerConfig::builder().environment("unit").port(8443).build().unwrap();
+        let yml = sc.to_yaml().unwrap();
+        assert!(yml.starts_with("config_version: 2\nenvironment: unit\nenvironments:\n  unit:\n    server:\n      host: localhost\n"));
+        let reloaded = ServerConfig::new("unit", Some(&yml)).unwrap();
+        assert_eq!(reloaded.effective_values(), sc.effective_values());
+        assert_eq!(reloaded.to_yaml().unwrap(), yml);
+    }
+
+    #[test]
+    pub fn test_svr_conf_new_layered(){
+        build_logger("BACHUETECH","SERVER_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        const BASE_CONTENT: &str = "prod:\n  server:\n    host: 10.0.0.1\n    port: 9999\nbase_only:\n  server:\n    port: 7000\n";
+        let sc = ServerConfig::new_layered("prod", BASE_CONTENT, ValidationMode::Lenient).unwrap();
+        assert_eq!(sc.get_tcp_listener(),"127.0.0.1:23333");
+        let sc = ServerConfig::new_layered("base_only", BASE_CONTENT, ValidationMode::Lenient).unwrap();
+        assert_eq!(sc.get_port(),7000);
+    }
+
+    #[test]
+    pub fn test_svr_conf_invalid_port(){
+        build_logger("BACHUETECH","SERVER_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        const YML_CONTENT: &str = "prod:\n  server:\n    port: 70000\n    secure: maybe\n";
+        let err = ServerConfig::new("prod", Some(YML_CONTENT)).unwrap_err();
+        assert!(err.to_string().contains("prod.server.port"));
+        assert!(err.to_string().contains("prod.server.secure"));
+        assert!(ServerConfig::json_schema().contains("65535"));
+    }
+
+    #[test]
+    pub fn test_svr_conf_unknown_keys_strict(){
+        build_logger("BACHUETECH","SERVER_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
+        const YML_CONTENT: &str = "prod:\n  server:\n    prot: 8080\n";
+        assert_eq!(ServerConfig::new("prod", Some(YML_CONTENT)).unwrap().get_port(),23339);
+        let err = ServerConfig::new_with_validation("prod", Some(YML_CONTENT), ValidationMode::Strict).unwrap_err();
+        assert!(err.to_string().contains("prod.server.prot: unknown key 'prot', did you mean 'port'?"));
+    }
 }
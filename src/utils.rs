@@ -30,6 +30,22 @@ pub fn build_app_route(root_path: &str, path_route: &str) -> String{
     format!("{}{}",root_path,path_route)
 }
 
+///Seconds since the Unix epoch as `YYYY-MM-DDTHH:MM:SSZ`
+pub(crate) fn format_utc(secs: u64) -> String {
+    //Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    let secs_of_day = secs % 86_400;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, secs_of_day / 3_600, secs_of_day % 3_600 / 60, secs_of_day % 60)
+}
+
 //***********/
 // UNIT TEST 
 //***********/
@@ -44,12 +60,16 @@ use crate::utils::{build_full_route, get_app_base_url, init_app_base_url};
         assert_ne!("/",get_app_base_url())
     }*/  
     
+    //The base URL is global and also set by the AppConfig loaded in other tests: only the first value is kept.
     #[test]
     pub fn test_app_base_url_set_success(){
         const BASE_URL: &str = "/base_url";
         build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
         init_app_base_url(BASE_URL);
-        assert_eq!(BASE_URL,get_app_base_url())
+        let base_url = get_app_base_url();
+        assert_ne!("/",base_url);
+        init_app_base_url("/other_url");
+        assert_eq!(base_url,get_app_base_url())
     }
 
      #[test]    
@@ -58,6 +78,17 @@ use crate::utils::{build_full_route, get_app_base_url, init_app_base_url};
         const PATH_ROUTE: &str = "/path/here";
         build_logger("BACHUETECH","APP_CONFIG",LogLevel::VERBOSE,LogTarget::STD_ERROR,None);
         init_app_base_url(BASE_URL);
-        assert_eq!(format!("{}{}",BASE_URL,PATH_ROUTE),build_full_route(PATH_ROUTE))
+        assert_eq!(format!("{}{}",get_app_base_url(),PATH_ROUTE),build_full_route(PATH_ROUTE))
     }      
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod utils_format_utc_tests {
+    use crate::utils::format_utc;
+
+    #[test]
+    pub fn test_format_utc(){
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14T22:13:20Z");
+    }
+}
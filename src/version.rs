@@ -0,0 +1,150 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+///Semantic version (`major.minor.patch[-pre][+build]`). A leading `v` is accepted and minor/patch default to 0 (e.g. `v1.2`).
+/// Ordering follows semver precedence; build metadata only breaks ties so that ordering agrees with equality.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    ///Pre-release identifiers (e.g. `rc.1`)
+    pub pre: Option<String>,
+    ///Build metadata (e.g. `20260105`)
+    pub build: Option<String>,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self{ major, minor, patch, pre: None, build: None }
+    }
+
+    ///Semver precedence: build metadata is ignored and a pre-release is lower than the release.
+    pub fn cmp_precedence(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => cmp_pre_release(a, b),
+            })
+    }
+}
+
+///Dot separated identifiers: numeric ones compare as numbers and are lower than alphanumeric ones.
+fn cmp_pre_release(a: &str, b: &str) -> Ordering {
+    let mut a_ids = a.split('.');
+    let mut b_ids = b.split('.');
+    loop {
+        match (a_ids.next(), b_ids.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => x.cmp(y),
+                };
+                if order != Ordering::Equal {
+                    return order;
+                }
+            },
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_precedence(other).then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid version '{}': expected major.minor.patch[-pre][+build].", value);
+        let trimmed = value.trim();
+        let trimmed = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+        let (rest, build) = match trimmed.split_once('+') {
+            Some((rest, build)) => (rest, Some(build)),
+            None => (trimmed, None),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (rest, None),
+        };
+        let valid_ids = |ids: &str| ids.split('.').all(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+        if pre.is_some_and(|p| !valid_ids(p)) || build.is_some_and(|b| !valid_ids(b)) {
+            return Err(invalid());
+        }
+
+        let numbers = core.split('.')
+            .map(|n| if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) { n.parse::<u64>().ok() } else { None })
+            .collect::<Option<Vec<u64>>>()
+            .ok_or_else(invalid)?;
+        match numbers.as_slice() {
+            [major] => Ok(Self::new(*major, 0, 0)),
+            [major, minor] => Ok(Self::new(*major, *minor, 0)),
+            [major, minor, patch] => Ok(Self::new(*major, *minor, *patch)),
+            _ => Err(invalid()),
+        }
+        .map(|v| Self{ pre: pre.map(|p| p.to_owned()), build: build.map(|b| b.to_owned()), ..v })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        if let Some(build) = &self.build {
+            write!(f, "+{}", build)?;
+        }
+        Ok(())
+    }
+}
+
+
+//***********/
+// UNIT TEST
+//***********/
+#[cfg(test)]
+mod version_tests {
+    use super::Version;
+
+    #[test]
+    pub fn test_parse_success(){
+        let v: Version = "v1.2.3-rc.1+build.5".parse().unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (1, 2, 3));
+        assert_eq!(v.pre.as_deref(), Some("rc.1"));
+        assert_eq!(v.build.as_deref(), Some("build.5"));
+        assert_eq!(v.to_string(), "1.2.3-rc.1+build.5");
+        assert_eq!("1.4".parse::<Version>().unwrap(), Version::new(1, 4, 0));
+        assert!("1.x".parse::<Version>().is_err());
+        assert!("1.2.3.4".parse::<Version>().is_err());
+        assert!("1.2.3-".parse::<Version>().is_err());
+        assert!("".parse::<Version>().is_err());
+    }
+
+    #[test]
+    pub fn test_ordering(){
+        let parse = |v: &str| v.parse::<Version>().unwrap();
+        assert!(parse("1.0.0-alpha") < parse("1.0.0-alpha.1"));
+        assert!(parse("1.0.0-alpha.1") < parse("1.0.0-alpha.beta"));
+        assert!(parse("1.0.0-beta.2") < parse("1.0.0-beta.11"));
+        assert!(parse("1.0.0-rc.1") < parse("1.0.0"));
+        assert!(parse("1.9.0") < parse("1.10.0"));
+        assert_eq!(parse("1.0.0+a").cmp_precedence(&parse("1.0.0+b")), std::cmp::Ordering::Equal);
+    }
+}